@@ -0,0 +1,14 @@
+//! `cargo-credential-lpop`: a `credential-process` provider for Cargo,
+//! registered in `.cargo/config.toml` as:
+//!
+//! ```toml
+//! [registries.my-registry]
+//! credential-provider = "cargo-credential-lpop"
+//! ```
+
+use lpop_keychain_native::cargo_credential;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    cargo_credential::run().await
+}
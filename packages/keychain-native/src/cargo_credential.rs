@@ -0,0 +1,190 @@
+//! Implements Cargo's `credential-process` stdin/stdout protocol on top of
+//! the platform `KeychainAccess` backend, so `lpop` can be registered as a
+//! `cargo login`/`cargo publish` credential provider for any registry.
+
+use crate::error::KeychainError;
+use crate::platform::{self, KeychainAccess};
+use crate::KeychainOptions;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+/// The `index_url` of the registry Cargo is asking us about.
+#[derive(Debug, Deserialize)]
+pub struct RegistryInfo {
+    pub index_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Get,
+    Store,
+    Logout,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CredentialRequest {
+    pub action: Action,
+    pub registry: RegistryInfo,
+    /// The token to persist for `Action::Store`. Cargo's credential-process
+    /// protocol sends this as part of the same request payload rather than
+    /// as a second, separate stream, so `handle_request` never needs to read
+    /// stdin itself — `run` already consumed all of it parsing `request`.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CredentialResponseKind {
+    Get,
+    Store,
+    Logout,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialSuccess {
+    pub kind: CredentialResponseKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialFailure {
+    pub kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub enum CredentialResponse {
+    Ok(CredentialSuccess),
+    Err(CredentialFailure),
+}
+
+/// Mirrors `GitPathResolver::generate_service_name`: derive a stable,
+/// collision-free service name for a registry from its index URL.
+pub fn service_name_for_registry(index_url: &str) -> String {
+    format!("cargo-registry:{}", index_url)
+}
+
+pub async fn handle_request(
+    keychain: &(dyn KeychainAccess + Send + Sync),
+    request: CredentialRequest,
+) -> CredentialResponse {
+    let service = service_name_for_registry(&request.registry.index_url);
+    let account = "";
+    let token = request.token;
+
+    match request.action {
+        Action::Get => match keychain.get_password(&service, account).await {
+            Ok(Some(token)) => CredentialResponse::Ok(CredentialSuccess {
+                kind: CredentialResponseKind::Get,
+                token: Some(token),
+                cache: Some("session".to_string()),
+            }),
+            Ok(None) => CredentialResponse::Err(CredentialFailure { kind: "not-found" }),
+            Err(KeychainError::NotFound(_)) => {
+                CredentialResponse::Err(CredentialFailure { kind: "not-found" })
+            }
+            Err(_) => CredentialResponse::Err(CredentialFailure { kind: "other" }),
+        },
+        Action::Store => {
+            let token = match token {
+                Some(token) => token,
+                None => return CredentialResponse::Err(CredentialFailure { kind: "other" }),
+            };
+            match keychain
+                .set_password(&service, account, token.trim_end())
+                .await
+            {
+                Ok(()) => CredentialResponse::Ok(CredentialSuccess {
+                    kind: CredentialResponseKind::Store,
+                    token: None,
+                    cache: None,
+                }),
+                Err(_) => CredentialResponse::Err(CredentialFailure { kind: "other" }),
+            }
+        }
+        Action::Logout => match keychain.delete_password(&service, account).await {
+            Ok(_) => CredentialResponse::Ok(CredentialSuccess {
+                kind: CredentialResponseKind::Logout,
+                token: None,
+                cache: None,
+            }),
+            Err(KeychainError::NotFound(_)) => {
+                CredentialResponse::Err(CredentialFailure { kind: "not-found" })
+            }
+            Err(_) => CredentialResponse::Err(CredentialFailure { kind: "other" }),
+        },
+    }
+}
+
+/// Entry point for the `cargo-credential-lpop` binary: read one JSON request
+/// from stdin, act on it via the platform keychain, write one JSON response
+/// to stdout.
+pub async fn run() -> io::Result<()> {
+    let keychain = platform::create_keychain_access(None::<KeychainOptions>)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+    run_with_io(io::stdin(), io::stdout(), keychain.as_ref()).await
+}
+
+/// Does the actual work of `run`, over an injected reader/writer/keychain so
+/// tests can drive the real request/response flow (including the single
+/// stdin read `handle_request`'s `Store` branch depends on) without touching
+/// the process's real stdio or the real platform keychain.
+async fn run_with_io(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    keychain: &(dyn KeychainAccess + Send + Sync),
+) -> io::Result<()> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    let request: CredentialRequest = match serde_json::from_str(&input) {
+        Ok(req) => req,
+        Err(_) => {
+            let response = CredentialResponse::Err(CredentialFailure { kind: "other" });
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+            return Ok(());
+        }
+    };
+
+    let response = handle_request(keychain, request).await;
+    writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_name_for_registry() {
+        assert_eq!(
+            service_name_for_registry("https://github.com/rust-lang/crates.io-index"),
+            "cargo-registry:https://github.com/rust-lang/crates.io-index"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_store_action_persists_the_full_token_from_stdin() {
+        let keychain = platform::fallback::FallbackKeychain::new(None).unwrap();
+        let index_url = "https://example.com/index";
+        let request = serde_json::json!({
+            "action": "store",
+            "registry": { "index_url": index_url },
+            "token": "super-secret-token",
+        });
+
+        let mut output = Vec::new();
+        run_with_io(request.to_string().as_bytes(), &mut output, &keychain)
+            .await
+            .unwrap();
+
+        let service = service_name_for_registry(index_url);
+        let stored = keychain.get_password(&service, "").await.unwrap();
+        assert_eq!(stored, Some("super-secret-token".to_string()));
+    }
+}
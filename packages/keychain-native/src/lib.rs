@@ -4,6 +4,8 @@ use napi_derive::napi;
 
 mod platform;
 mod error;
+pub mod cargo_credential;
+pub mod remote_auth;
 
 use error::KeychainError;
 use platform::{KeychainAccess, KeychainEntry as PlatformEntry};
@@ -15,6 +17,43 @@ pub struct KeychainEntry {
     pub password: String,
 }
 
+/// Extra attributes a `PlatformKeychain` backend may record alongside a
+/// password. Not every field is meaningful on every backend (e.g.
+/// `code_signing_info` only applies to macOS); backends persist whatever
+/// they can and leave the rest `None`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct KeychainMetadata {
+    pub created_at: Option<i64>,
+    pub modified_at: Option<i64>,
+    pub label: Option<String>,
+    pub comment: Option<String>,
+    pub team_id: Option<String>,
+    pub code_signing_info: Option<CodeSigningInfo>,
+    pub access_group: Option<String>,
+    pub synchronizable: Option<bool>,
+    /// Base64-encoded Argon2 salt used to derive the key that encrypted this
+    /// entry's password, set by `EncryptingPlatformKeychain`. Absent when
+    /// `KeychainOptions.encryption` isn't configured.
+    pub encryption_salt: Option<String>,
+}
+
+/// Code-signing identity recovered from the binary that wrote an entry, used
+/// on macOS to detect when a rebuild/resign changed the signing identity.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct CodeSigningInfo {
+    pub team_id: Option<String>,
+    pub identifier: Option<String>,
+}
+
+/// Filters for `PlatformKeychain::find_entries`.
+#[derive(Clone, Debug, Default)]
+pub struct FindQuery {
+    pub account_prefix: Option<String>,
+    pub environment: Option<String>,
+    pub team_id: Option<String>,
+    pub access_group: Option<String>,
+}
+
 #[napi(object)]
 pub struct KeychainOptions {
     /// Team ID for macOS code signing (e.g., "ABC123XYZ")
@@ -23,6 +62,57 @@ pub struct KeychainOptions {
     pub access_group: Option<String>,
     /// Whether to synchronize with iCloud Keychain
     pub synchronizable: Option<bool>,
+    /// Which concrete backend to use on macOS. Defaults to the native
+    /// `security-framework` path; pass `"security-cli"` to shell out to
+    /// `/usr/bin/security` instead, which keeps a stable signing identity
+    /// across debug rebuilds and avoids repeated "allow access" prompts.
+    pub backend: Option<String>,
+    /// When set, wraps the selected backend with client-side AES-256-GCM
+    /// envelope encryption so the value written to the OS keychain (and any
+    /// `synchronizable` copy in iCloud Keychain) is ciphertext, not plaintext.
+    pub encryption_key: Option<String>,
+    /// On macOS, store entries as `kSecClassInternetPassword` with
+    /// structured URL attributes (server/protocol/path/port) instead of a
+    /// generic password, since `service` strings are actually git remote
+    /// URLs. Ignored on other platforms.
+    pub use_internet_password: Option<bool>,
+    /// Service namespace the `"object-store"` backend scopes its entries
+    /// under (analogous to `service` for the local keychain backends).
+    pub service: Option<String>,
+    /// S3/Garage-compatible endpoint for the `"object-store"` backend. Falls
+    /// back to `LPOP_SYNC_ENDPOINT` when unset.
+    pub object_store_endpoint: Option<String>,
+    /// Bucket for the `"object-store"` backend. Falls back to
+    /// `LPOP_SYNC_BUCKET` when unset.
+    pub object_store_bucket: Option<String>,
+    /// Access key for the `"object-store"` backend. Falls back to
+    /// `LPOP_SYNC_ACCESS_KEY` when unset.
+    pub object_store_access_key: Option<String>,
+    /// Secret key for the `"object-store"` backend. Falls back to
+    /// `LPOP_SYNC_SECRET_KEY` when unset.
+    pub object_store_secret_key: Option<String>,
+    /// When set, wraps the `PlatformKeychain` `create_keychain` returns with
+    /// Argon2id + XChaCha20-Poly1305 envelope encryption, so `password` is
+    /// ciphertext before it ever reaches the platform store (local keychain
+    /// DB or `"object-store"` bucket alike). Distinct from `encryption_key`,
+    /// which encrypts the lower-level `KeychainAccess` path instead.
+    pub encryption: Option<Passphrase>,
+    /// When set, wraps the `PlatformKeychain` `create_keychain` returns with
+    /// `SyncingPlatformKeychain`, recording every mutation in an append-only,
+    /// checkpointed operation log tagged with this node's id so multiple
+    /// devices can later merge their logs via `SyncingPlatformKeychain::sync`.
+    /// The id just needs to be stable and unique per device/process — e.g. a
+    /// hostname or a generated UUID persisted locally.
+    pub sync_node_id: Option<String>,
+}
+
+/// A passphrase used to derive a `PlatformKeychain` encryption key. Its own
+/// napi struct (rather than a bare `String` field) so `KeychainOptions`
+/// leaves room for future KDF tuning without another breaking field.
+#[napi(object)]
+#[derive(Clone)]
+pub struct Passphrase {
+    pub value: String,
 }
 
 #[napi]
@@ -65,6 +155,37 @@ impl Keychain {
             .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))
     }
 
+    /// Verifies `token` (or a `username:password` combo string, when
+    /// `username` is omitted) against `host`'s API before storing it,
+    /// so a bad secret never gets persisted silently. Returns the verified
+    /// account name.
+    #[napi]
+    pub async fn login_remote(
+        &self,
+        host: String,
+        username: Option<String>,
+        token: String,
+    ) -> napi::Result<String> {
+        let (_combo_username, secret) = remote_auth::split_combo_credential(&token);
+        let secret = if username.is_some() { token } else { secret };
+
+        let verified_username = remote_auth::verify_remote_token(&host, &secret)
+            .await
+            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))?;
+
+        // Store under the account the API call actually verified the token
+        // belongs to, not whatever the caller passed in — a mismatched
+        // `username` (typo, stale config, a token valid for a different
+        // account) would otherwise silently file a good token under the
+        // wrong identity while still reporting success.
+        self.platform
+            .set_password(&host, &verified_username, &secret)
+            .await
+            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))?;
+
+        Ok(verified_username)
+    }
+
     #[napi]
     pub async fn delete_password(
         &self,
@@ -116,4 +237,26 @@ impl Keychain {
             })
             .collect())
     }
+
+    /// Finds every entry belonging to `server` (a bare host, e.g.
+    /// `github.com`), across every owner/repo/environment stored for it.
+    #[napi]
+    pub async fn find_by_server(
+        &self,
+        server: String,
+    ) -> napi::Result<Vec<KeychainEntry>> {
+        let entries = self.platform
+            .find_by_server(&server)
+            .await
+            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))?;
+
+        Ok(entries
+            .into_iter()
+            .map(|e| KeychainEntry {
+                service: e.service,
+                account: e.account,
+                password: e.password,
+            })
+            .collect())
+    }
 }
\ No newline at end of file
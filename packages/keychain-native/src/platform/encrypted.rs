@@ -0,0 +1,449 @@
+use crate::error::{KeychainError, Result};
+use crate::platform::{KeychainAccess, KeychainEntry, PlatformKeychain};
+use crate::{FindQuery, KeychainMetadata};
+use aes_gcm::aead::Aead as _;
+use aes_gcm::{Aes256Gcm, KeyInit as _, Nonce};
+use argon2::Argon2;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use chacha20poly1305::aead::Aead as XAead;
+use chacha20poly1305::{KeyInit as XKeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+
+/// Marks an entry as envelope-encrypted by this layer. Legacy plaintext
+/// entries (or anything that isn't valid base64 / doesn't start with this
+/// byte) are returned unchanged so existing secrets keep working.
+///
+/// v1 entries derived their key via unsalted SHA-256 of the passphrase and
+/// carried no salt in the payload; v2 derives via Argon2id with a random
+/// salt stored alongside the ciphertext. v1 payloads no longer decrypt
+/// (the key derivation differs) and come back as opaque ciphertext rather
+/// than panicking — re-`set_password` those entries to upgrade them.
+const VERSION_BYTE: u8 = 0x02;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Wraps any `KeychainAccess` backend with client-side AES-256-GCM envelope
+/// encryption, so the value that actually reaches the OS keychain (and, for
+/// `synchronizable: true` entries, iCloud Keychain) is ciphertext rather than
+/// plaintext.
+pub struct EncryptingKeychain {
+    inner: Box<dyn KeychainAccess + Send + Sync>,
+    passphrase: String,
+}
+
+impl EncryptingKeychain {
+    pub fn new(inner: Box<dyn KeychainAccess + Send + Sync>, encryption_key: &str) -> Result<Self> {
+        Ok(Self {
+            inner,
+            passphrase: encryption_key.to_string(),
+        })
+    }
+
+    fn derive_key(&self, salt: &[u8]) -> Result<[u8; 32]> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(self.passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| KeychainError::InvalidData(format!("key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let key = self.derive_key(&salt)?;
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| KeychainError::InvalidParameter(format!("invalid encryption key: {}", e)))?;
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext = cipher
+            .encrypt(nonce, plaintext.as_bytes())
+            .map_err(|e| KeychainError::InvalidData(format!("failed to encrypt value: {}", e)))?;
+
+        let mut payload = Vec::with_capacity(1 + SALT_LEN + NONCE_LEN + ciphertext.len());
+        payload.push(VERSION_BYTE);
+        payload.extend_from_slice(&salt);
+        payload.extend_from_slice(&nonce_bytes);
+        payload.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(payload))
+    }
+
+    fn decrypt(&self, stored: &str) -> Result<String> {
+        let raw = match STANDARD.decode(stored) {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(stored.to_string()),
+        };
+        if raw.first() != Some(&VERSION_BYTE) || raw.len() < 1 + SALT_LEN + NONCE_LEN {
+            // Either a legacy plaintext entry, or a v1 envelope-encrypted
+            // entry from before the Argon2id migration; neither can be
+            // decrypted with today's key derivation, so return as-is rather
+            // than erroring the whole call.
+            return Ok(stored.to_string());
+        }
+
+        let salt = &raw[1..1 + SALT_LEN];
+        let nonce = Nonce::from_slice(&raw[1 + SALT_LEN..1 + SALT_LEN + NONCE_LEN]);
+        let ciphertext = &raw[1 + SALT_LEN + NONCE_LEN..];
+        let key = self.derive_key(salt)?;
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| KeychainError::InvalidParameter(format!("invalid encryption key: {}", e)))?;
+        let plaintext = cipher.decrypt(nonce, ciphertext).map_err(|_| {
+            KeychainError::InvalidData("failed to authenticate encrypted keychain entry".to_string())
+        })?;
+
+        String::from_utf8(plaintext).map_err(|e| KeychainError::InvalidData(e.to_string()))
+    }
+
+    fn decrypt_entry(&self, entry: KeychainEntry) -> Result<KeychainEntry> {
+        Ok(KeychainEntry {
+            password: self.decrypt(&entry.password)?,
+            ..entry
+        })
+    }
+}
+
+#[async_trait]
+impl KeychainAccess for EncryptingKeychain {
+    async fn set_password(&self, service: &str, account: &str, password: &str) -> Result<()> {
+        let ciphertext = self.encrypt(password)?;
+        self.inner.set_password(service, account, &ciphertext).await
+    }
+
+    async fn get_password(&self, service: &str, account: &str) -> Result<Option<String>> {
+        match self.inner.get_password(service, account).await? {
+            Some(stored) => Ok(Some(self.decrypt(&stored)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete_password(&self, service: &str, account: &str) -> Result<bool> {
+        self.inner.delete_password(service, account).await
+    }
+
+    async fn find_credentials(&self, service: &str) -> Result<Vec<KeychainEntry>> {
+        self.inner
+            .find_credentials(service)
+            .await?
+            .into_iter()
+            .map(|e| self.decrypt_entry(e))
+            .collect()
+    }
+
+    async fn find_by_account(&self, account: &str) -> Result<Vec<KeychainEntry>> {
+        self.inner
+            .find_by_account(account)
+            .await?
+            .into_iter()
+            .map(|e| self.decrypt_entry(e))
+            .collect()
+    }
+
+    async fn find_by_server(&self, server: &str) -> Result<Vec<KeychainEntry>> {
+        self.inner
+            .find_by_server(server)
+            .await?
+            .into_iter()
+            .map(|e| self.decrypt_entry(e))
+            .collect()
+    }
+}
+
+const PLATFORM_SALT_LEN: usize = 16;
+const PLATFORM_NONCE_LEN: usize = 24;
+
+/// Wraps any `PlatformKeychain` backend with Argon2id + XChaCha20-Poly1305
+/// envelope encryption, so `password` is ciphertext before it ever reaches
+/// the platform store — the local keychain DB or a `"object-store"` bucket
+/// alike. Like `EncryptingKeychain`, this layer derives its key via Argon2id
+/// from a random salt generated per entry, recorded in
+/// `KeychainMetadata.encryption_salt` rather than embedded in the payload
+/// itself, so a leaked store can't be dictionary-attacked with a single
+/// rainbow table.
+pub struct EncryptingPlatformKeychain {
+    inner: Box<dyn PlatformKeychain>,
+    passphrase: String,
+}
+
+impl EncryptingPlatformKeychain {
+    pub fn new(inner: Box<dyn PlatformKeychain>, passphrase: &str) -> Result<Self> {
+        Ok(Self {
+            inner,
+            passphrase: passphrase.to_string(),
+        })
+    }
+
+    fn derive_platform_key(&self, salt: &[u8]) -> Result<[u8; 32]> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(self.passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| KeychainError::InvalidData(format!("key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+
+    /// Encrypts `password`, returning the ciphertext blob (to store as the
+    /// entry's `password`) and the salt used to derive its key (to store in
+    /// `KeychainMetadata.encryption_salt`).
+    fn encrypt(&self, password: &str) -> Result<(String, String)> {
+        let mut salt = [0u8; PLATFORM_SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let key = self.derive_platform_key(&salt)?;
+
+        let mut nonce_bytes = [0u8; PLATFORM_NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+        let cipher = XChaCha20Poly1305::new_from_slice(&key)
+            .map_err(|e| KeychainError::InvalidParameter(format!("invalid derived key length: {}", e)))?;
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce_bytes), password.as_bytes())
+            .map_err(|e| KeychainError::InvalidData(format!("failed to encrypt password: {}", e)))?;
+
+        let mut payload = Vec::with_capacity(PLATFORM_NONCE_LEN + ciphertext.len());
+        payload.extend_from_slice(&nonce_bytes);
+        payload.extend_from_slice(&ciphertext);
+
+        Ok((STANDARD.encode(payload), STANDARD.encode(salt)))
+    }
+
+    fn decrypt(&self, stored: &str, salt: &str) -> Result<String> {
+        let salt = STANDARD
+            .decode(salt)
+            .map_err(|e| KeychainError::InvalidData(format!("invalid encryption salt: {}", e)))?;
+        let payload = STANDARD
+            .decode(stored)
+            .map_err(|e| KeychainError::InvalidData(format!("invalid encrypted password: {}", e)))?;
+        if payload.len() < PLATFORM_NONCE_LEN {
+            return Err(KeychainError::InvalidData("encrypted password too short".to_string()));
+        }
+
+        let key = self.derive_platform_key(&salt)?;
+        let cipher = XChaCha20Poly1305::new_from_slice(&key)
+            .map_err(|e| KeychainError::InvalidParameter(format!("invalid derived key length: {}", e)))?;
+        let (nonce, ciphertext) = payload.split_at(PLATFORM_NONCE_LEN);
+        let plaintext = cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| KeychainError::InvalidData("wrong passphrase or corrupt entry".to_string()))?;
+
+        String::from_utf8(plaintext).map_err(|e| KeychainError::InvalidData(e.to_string()))
+    }
+
+    fn decrypt_entry(&self, entry: KeychainEntry) -> Result<KeychainEntry> {
+        let salt = entry.metadata.as_ref().and_then(|m| m.encryption_salt.clone());
+        let Some(salt) = salt else {
+            // Legacy plaintext entry written before encryption was enabled.
+            return Ok(entry);
+        };
+
+        Ok(KeychainEntry {
+            password: self.decrypt(&entry.password, &salt)?,
+            ..entry
+        })
+    }
+}
+
+#[async_trait]
+impl PlatformKeychain for EncryptingPlatformKeychain {
+    async fn set_password(
+        &self,
+        account: &str,
+        password: &str,
+        metadata: Option<KeychainMetadata>,
+    ) -> Result<()> {
+        let (ciphertext, salt) = self.encrypt(password)?;
+        let metadata = KeychainMetadata {
+            encryption_salt: Some(salt),
+            ..metadata.unwrap_or_default()
+        };
+        self.inner.set_password(account, &ciphertext, Some(metadata)).await
+    }
+
+    async fn get_password(&self, account: &str) -> Result<Option<String>> {
+        match self.get_entry(account).await? {
+            Some(entry) => Ok(Some(entry.password)),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete_password(&self, account: &str) -> Result<bool> {
+        self.inner.delete_password(account).await
+    }
+
+    async fn get_entry(&self, account: &str) -> Result<Option<KeychainEntry>> {
+        match self.inner.get_entry(account).await? {
+            Some(entry) => Ok(Some(self.decrypt_entry(entry)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_entries(&self, query: Option<FindQuery>) -> Result<Vec<KeychainEntry>> {
+        self.inner
+            .find_entries(query)
+            .await?
+            .into_iter()
+            .map(|e| self.decrypt_entry(e))
+            .collect()
+    }
+
+    fn get_platform_info(&self) -> &'static str {
+        "encrypted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::fallback::FallbackKeychain;
+
+    fn encrypting_fallback(key: &str) -> EncryptingKeychain {
+        let inner = Box::new(FallbackKeychain::new(None).unwrap());
+        EncryptingKeychain::new(inner, key).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip_through_encryption() {
+        let keychain = encrypting_fallback("correct-horse-battery-staple");
+        keychain
+            .set_password("svc", "acct", "my-secret")
+            .await
+            .unwrap();
+
+        let value = keychain.get_password("svc", "acct").await.unwrap();
+        assert_eq!(value, Some("my-secret".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_plaintext_entry_passes_through() {
+        let keychain = encrypting_fallback("correct-horse-battery-staple");
+        // Simulate a pre-existing unencrypted entry written before this layer existed.
+        keychain
+            .inner
+            .set_password("svc", "acct", "plain-legacy-value")
+            .await
+            .unwrap();
+
+        let value = keychain.get_password("svc", "acct").await.unwrap();
+        assert_eq!(value, Some("plain-legacy-value".to_string()));
+    }
+
+    #[test]
+    fn test_derive_key_is_deterministic_given_the_same_salt() {
+        let salt = [7u8; SALT_LEN];
+        let a = encrypting_fallback("passphrase");
+        let b = encrypting_fallback("passphrase");
+        let c = encrypting_fallback("other");
+        assert_eq!(a.derive_key(&salt).unwrap(), b.derive_key(&salt).unwrap());
+        assert_ne!(a.derive_key(&salt).unwrap(), c.derive_key(&salt).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_wrong_passphrase_fails_to_decrypt() {
+        let keychain = encrypting_fallback("correct-horse-battery-staple");
+        keychain
+            .set_password("svc", "acct", "my-secret")
+            .await
+            .unwrap();
+
+        let other = encrypting_fallback("wrong-passphrase");
+        // Reuse the same backing store by copying the stored ciphertext over directly.
+        let stored = keychain.inner.get_password("svc", "acct").await.unwrap().unwrap();
+        other.inner.set_password("svc", "acct", &stored).await.unwrap();
+
+        assert!(other.get_password("svc", "acct").await.is_err());
+    }
+
+    /// Minimal in-memory `PlatformKeychain`, used only to exercise
+    /// `EncryptingPlatformKeychain` without a real OS keychain or object store.
+    #[derive(Default)]
+    struct MockPlatformKeychain {
+        entries: std::sync::Mutex<std::collections::HashMap<String, KeychainEntry>>,
+    }
+
+    #[async_trait]
+    impl PlatformKeychain for MockPlatformKeychain {
+        async fn set_password(
+            &self,
+            account: &str,
+            password: &str,
+            metadata: Option<KeychainMetadata>,
+        ) -> Result<()> {
+            self.entries.lock().unwrap().insert(
+                account.to_string(),
+                KeychainEntry {
+                    service: "mock".to_string(),
+                    account: account.to_string(),
+                    password: password.to_string(),
+                    metadata,
+                },
+            );
+            Ok(())
+        }
+
+        async fn get_password(&self, account: &str) -> Result<Option<String>> {
+            Ok(self.entries.lock().unwrap().get(account).map(|e| e.password.clone()))
+        }
+
+        async fn delete_password(&self, account: &str) -> Result<bool> {
+            Ok(self.entries.lock().unwrap().remove(account).is_some())
+        }
+
+        async fn get_entry(&self, account: &str) -> Result<Option<KeychainEntry>> {
+            Ok(self.entries.lock().unwrap().get(account).cloned())
+        }
+
+        async fn find_entries(&self, _query: Option<FindQuery>) -> Result<Vec<KeychainEntry>> {
+            Ok(self.entries.lock().unwrap().values().cloned().collect())
+        }
+
+        fn get_platform_info(&self) -> &'static str {
+            "mock"
+        }
+    }
+
+    fn encrypting_mock(passphrase: &str) -> EncryptingPlatformKeychain {
+        let inner = Box::new(MockPlatformKeychain::default());
+        EncryptingPlatformKeychain::new(inner, passphrase).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_platform_roundtrip_through_encryption() {
+        let keychain = encrypting_mock("correct-horse-battery-staple");
+        keychain.set_password("acct", "my-secret", None).await.unwrap();
+
+        let value = keychain.get_password("acct").await.unwrap();
+        assert_eq!(value, Some("my-secret".to_string()));
+
+        let entry = keychain.get_entry("acct").await.unwrap().unwrap();
+        assert_ne!(entry.password, "my-secret", "stored password must be ciphertext");
+        assert!(entry.metadata.unwrap().encryption_salt.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_platform_wrong_passphrase_fails_to_decrypt() {
+        let keychain = encrypting_mock("correct-horse-battery-staple");
+        keychain.set_password("acct", "my-secret", None).await.unwrap();
+
+        let other = encrypting_mock("wrong-passphrase");
+        // Copy the stored entry from `keychain`'s inner store into `other`'s.
+        let stored = keychain.inner.get_entry("acct").await.unwrap().unwrap();
+        other.inner.set_password("acct", &stored.password, stored.metadata).await.unwrap();
+
+        let result = other.get_password("acct").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_platform_legacy_plaintext_entry_passes_through() {
+        let keychain = encrypting_mock("correct-horse-battery-staple");
+        keychain
+            .inner
+            .set_password("acct", "plain-legacy-value", None)
+            .await
+            .unwrap();
+
+        let value = keychain.get_password("acct").await.unwrap();
+        assert_eq!(value, Some("plain-legacy-value".to_string()));
+    }
+}
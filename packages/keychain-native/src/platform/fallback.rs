@@ -1,5 +1,5 @@
 use crate::error::{KeychainError, Result};
-use crate::platform::{KeychainAccess, KeychainEntry};
+use crate::platform::{service_matches_server, KeychainAccess, KeychainEntry};
 use crate::KeychainOptions;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -62,4 +62,18 @@ impl KeychainAccess for FallbackKeychain {
             .collect();
         Ok(entries)
     }
+
+    async fn find_by_server(&self, server: &str) -> Result<Vec<KeychainEntry>> {
+        let storage = self.storage.lock().unwrap();
+        let entries: Vec<KeychainEntry> = storage
+            .iter()
+            .filter(|((s, _), _)| service_matches_server(s, server))
+            .map(|((s, a), p)| KeychainEntry {
+                service: s.clone(),
+                account: a.clone(),
+                password: p.clone(),
+            })
+            .collect();
+        Ok(entries)
+    }
 }
\ No newline at end of file
@@ -35,4 +35,8 @@ impl KeychainAccess for LinuxKeychain {
     async fn find_by_account(&self, _account: &str) -> Result<Vec<KeychainEntry>> {
         Err(KeychainError::Unsupported("Not implemented".to_string()))
     }
+
+    async fn find_by_server(&self, _server: &str) -> Result<Vec<KeychainEntry>> {
+        Err(KeychainError::Unsupported("Not implemented".to_string()))
+    }
 }
\ No newline at end of file
@@ -2,23 +2,58 @@ use crate::error::{KeychainError, Result};
 use crate::platform::{KeychainAccess, KeychainEntry};
 use crate::KeychainOptions;
 use async_trait::async_trait;
+use core_foundation::array::CFArray;
 use core_foundation::base::{CFRelease, TCFType};
 use core_foundation::boolean::CFBoolean;
 use core_foundation::dictionary::CFDictionary;
+use core_foundation::number::CFNumber;
 use core_foundation::string::CFString;
 use security_framework::item::{ItemClass, ItemSearchOptions, SearchResult};
 use security_framework::passwords::delete_generic_password;
 use security_framework_sys::base::errSecItemNotFound;
 use security_framework_sys::item::*;
-use security_framework_sys::keychain_item::SecItemCopyMatching;
+use security_framework_sys::keychain_item::{SecItemCopyMatching, SecItemDelete};
 use std::collections::HashMap;
 use std::os::raw::c_void;
 use std::ptr;
+use url::Url;
 
 pub struct MacOSKeychain {
     team_id: Option<String>,
     access_group: Option<String>,
     synchronizable: bool,
+    use_internet_password: bool,
+}
+
+/// The structured URL attributes an `kSecClassInternetPassword` item is
+/// keyed by, parsed out of a `service` string. `lpop` service names are
+/// actually git remote URLs (e.g. `github.com/loggipop/lpop?env=development`),
+/// which group far more usefully in Keychain Access as internet passwords
+/// than as an opaque generic-password service string.
+struct ServiceUrl {
+    server: String,
+    protocol: String,
+    path: String,
+    port: Option<u16>,
+}
+
+fn parse_service_as_url(service: &str) -> Option<ServiceUrl> {
+    let repo_part = service.split("?env=").next().unwrap_or(service);
+    let with_scheme = if repo_part.contains("://") {
+        repo_part.to_string()
+    } else {
+        format!("https://{}", repo_part)
+    };
+
+    let url = Url::parse(&with_scheme).ok()?;
+    let server = url.host_str()?.to_string();
+
+    Some(ServiceUrl {
+        server,
+        protocol: url.scheme().to_string(),
+        path: url.path().to_string(),
+        port: url.port(),
+    })
 }
 
 impl MacOSKeychain {
@@ -27,36 +62,89 @@ impl MacOSKeychain {
             team_id: None,
             access_group: None,
             synchronizable: None,
+            backend: None,
+            encryption_key: None,
+            use_internet_password: None,
+            service: None,
+            object_store_endpoint: None,
+            object_store_bucket: None,
+            object_store_access_key: None,
+            object_store_secret_key: None,
+            encryption: None,
+            sync_node_id: None,
         });
 
         Ok(Self {
             team_id: options.team_id,
             access_group: options.access_group,
             synchronizable: options.synchronizable.unwrap_or(false),
+            use_internet_password: options.use_internet_password.unwrap_or(false),
         })
     }
 
     fn build_base_query(&self, service: &str, account: Option<&str>) -> HashMap<CFString, CFString> {
         let mut query = HashMap::new();
-        
+
         // Basic query parameters
         query.insert(
             unsafe { CFString::wrap_under_get_rule(kSecClass) },
             unsafe { CFString::wrap_under_get_rule(kSecClassGenericPassword) },
         );
-        
+
         query.insert(
             unsafe { CFString::wrap_under_get_rule(kSecAttrService) },
             CFString::new(service),
         );
-        
+
+        if let Some(account) = account {
+            query.insert(
+                unsafe { CFString::wrap_under_get_rule(kSecAttrAccount) },
+                CFString::new(account),
+            );
+        }
+
+        self.add_shared_attrs(&mut query);
+        query
+    }
+
+    /// Same as `build_base_query` but for `kSecClassInternetPassword`, used
+    /// when `use_internet_password` is enabled and `service` parses as a URL.
+    fn build_internet_password_query(
+        &self,
+        url: &ServiceUrl,
+        account: Option<&str>,
+    ) -> HashMap<CFString, CFString> {
+        let mut query = HashMap::new();
+
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecClass) },
+            unsafe { CFString::wrap_under_get_rule(kSecClassInternetPassword) },
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecAttrServer) },
+            CFString::new(&url.server),
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecAttrProtocol) },
+            CFString::new(&url.protocol),
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecAttrPath) },
+            CFString::new(&url.path),
+        );
+
         if let Some(account) = account {
             query.insert(
                 unsafe { CFString::wrap_under_get_rule(kSecAttrAccount) },
                 CFString::new(account),
             );
         }
-        
+
+        self.add_shared_attrs(&mut query);
+        query
+    }
+
+    fn add_shared_attrs(&self, query: &mut HashMap<CFString, CFString>) {
         // Add access group if specified
         if let Some(access_group) = &self.access_group {
             // On macOS, the access group should include the team ID
@@ -65,13 +153,13 @@ impl MacOSKeychain {
             } else {
                 access_group.clone()
             };
-            
+
             query.insert(
                 unsafe { CFString::wrap_under_get_rule(kSecAttrAccessGroup) },
                 CFString::new(&full_access_group),
             );
         }
-        
+
         // Add synchronizable flag if enabled
         if self.synchronizable {
             query.insert(
@@ -79,8 +167,18 @@ impl MacOSKeychain {
                 unsafe { CFString::wrap_under_get_rule(kCFBooleanTrue as *const c_void) },
             );
         }
-        
-        query
+    }
+
+    /// Port, unlike the other internet-password attributes, is a number
+    /// rather than a string, so it can't live in the `HashMap<CFString,
+    /// CFString>` the other builders use; it's added separately when present.
+    fn maybe_insert_port(dict_pairs: &mut Vec<(CFString, CFNumber)>, url: &ServiceUrl) {
+        if let Some(port) = url.port {
+            dict_pairs.push((
+                unsafe { CFString::wrap_under_get_rule(kSecAttrPort) },
+                CFNumber::from(port as i32),
+            ));
+        }
     }
 }
 
@@ -89,24 +187,65 @@ impl KeychainAccess for MacOSKeychain {
     async fn set_password(&self, service: &str, account: &str, password: &str) -> Result<()> {
         // First try to delete any existing password
         let _ = self.delete_password(service, account).await;
-        
+
+        if self.use_internet_password {
+            if let Some(url) = parse_service_as_url(service) {
+                let mut query = self.build_internet_password_query(&url, Some(account));
+                query.insert(
+                    unsafe { CFString::wrap_under_get_rule(kSecValueData) },
+                    CFString::new(password),
+                );
+                query.insert(
+                    unsafe { CFString::wrap_under_get_rule(kSecAttrLabel) },
+                    CFString::new(&format!("{} ({})", service, account)),
+                );
+
+                let mut port_pairs = Vec::new();
+                Self::maybe_insert_port(&mut port_pairs, &url);
+
+                let dict = if port_pairs.is_empty() {
+                    CFDictionary::from_CFType_pairs(&query)
+                } else {
+                    // Merge the string-valued and number-valued (port)
+                    // attributes by building the dictionary from erased
+                    // CFType pairs instead of the fixed-type HashMap above.
+                    let mut pairs: Vec<(CFString, core_foundation::base::CFType)> = query
+                        .into_iter()
+                        .map(|(k, v)| (k, v.as_CFType()))
+                        .collect();
+                    pairs.extend(port_pairs.into_iter().map(|(k, v)| (k, v.as_CFType())));
+                    CFDictionary::from_CFType_pairs(&pairs)
+                };
+
+                let result = unsafe { SecItemAdd(dict.as_concrete_TypeRef(), ptr::null_mut()) };
+                return if result == 0 {
+                    Ok(())
+                } else {
+                    Err(KeychainError::PlatformError(format!(
+                        "Failed to add internet password item: OSStatus {}",
+                        result
+                    )))
+                };
+            }
+        }
+
         // Build the query with the password
         let mut query = self.build_base_query(service, Some(account));
         query.insert(
             unsafe { CFString::wrap_under_get_rule(kSecValueData) },
             CFString::new(password),
         );
-        
+
         // Add label for better keychain UI display
         query.insert(
             unsafe { CFString::wrap_under_get_rule(kSecAttrLabel) },
             CFString::new(&format!("{} ({})", service, account)),
         );
-        
+
         // Create the dictionary and add the item
         let dict = CFDictionary::from_CFType_pairs(&query);
         let result = unsafe { SecItemAdd(dict.as_concrete_TypeRef(), ptr::null_mut()) };
-        
+
         if result == 0 {
             Ok(())
         } else {
@@ -118,24 +257,31 @@ impl KeychainAccess for MacOSKeychain {
     }
 
     async fn get_password(&self, service: &str, account: &str) -> Result<Option<String>> {
-        let mut query = self.build_base_query(service, Some(account));
-        
+        let mut query = if self.use_internet_password {
+            match parse_service_as_url(service) {
+                Some(url) => self.build_internet_password_query(&url, Some(account)),
+                None => self.build_base_query(service, Some(account)),
+            }
+        } else {
+            self.build_base_query(service, Some(account))
+        };
+
         // Request the password data
         query.insert(
             unsafe { CFString::wrap_under_get_rule(kSecReturnData) },
             unsafe { CFString::wrap_under_get_rule(kCFBooleanTrue as *const c_void) },
         );
-        
+
         // Limit to one result
         query.insert(
             unsafe { CFString::wrap_under_get_rule(kSecMatchLimit) },
             unsafe { CFString::wrap_under_get_rule(kSecMatchLimitOne) },
         );
-        
+
         let dict = CFDictionary::from_CFType_pairs(&query);
         let mut result_ptr: *mut c_void = ptr::null_mut();
         let status = unsafe { SecItemCopyMatching(dict.as_concrete_TypeRef(), &mut result_ptr) };
-        
+
         if status == errSecItemNotFound {
             Ok(None)
         } else if status == 0 && !result_ptr.is_null() {
@@ -153,6 +299,22 @@ impl KeychainAccess for MacOSKeychain {
     }
 
     async fn delete_password(&self, service: &str, account: &str) -> Result<bool> {
+        if self.use_internet_password {
+            if let Some(url) = parse_service_as_url(service) {
+                let query = self.build_internet_password_query(&url, Some(account));
+                let dict = CFDictionary::from_CFType_pairs(&query);
+                let status = unsafe { SecItemDelete(dict.as_concrete_TypeRef()) };
+                return match status {
+                    0 => Ok(true),
+                    s if s == errSecItemNotFound => Ok(false),
+                    s => Err(KeychainError::PlatformError(format!(
+                        "Failed to delete internet password item: OSStatus {}",
+                        s
+                    ))),
+                };
+            }
+        }
+
         match delete_generic_password(service, account) {
             Ok(_) => Ok(true),
             Err(e) if e.code() == errSecItemNotFound => Ok(false),
@@ -230,4 +392,71 @@ impl KeychainAccess for MacOSKeychain {
             ))),
         }
     }
-}
\ No newline at end of file
+
+    /// Finds internet password items grouped under `server`, e.g. all
+    /// credentials for `github.com` regardless of owner/repo/environment.
+    async fn find_by_server(&self, server: &str) -> Result<Vec<KeychainEntry>> {
+        let mut query = HashMap::new();
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecClass) },
+            unsafe { CFString::wrap_under_get_rule(kSecClassInternetPassword) },
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecAttrServer) },
+            CFString::new(server),
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecReturnAttributes) },
+            unsafe { CFString::wrap_under_get_rule(kCFBooleanTrue as *const c_void) },
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecReturnData) },
+            unsafe { CFString::wrap_under_get_rule(kCFBooleanTrue as *const c_void) },
+        );
+        query.insert(
+            unsafe { CFString::wrap_under_get_rule(kSecMatchLimit) },
+            unsafe { CFString::wrap_under_get_rule(kSecMatchLimitAll) },
+        );
+
+        let dict = CFDictionary::from_CFType_pairs(&query);
+        let mut result_ptr: *mut c_void = ptr::null_mut();
+        let status = unsafe { SecItemCopyMatching(dict.as_concrete_TypeRef(), &mut result_ptr) };
+
+        if status == errSecItemNotFound {
+            return Ok(Vec::new());
+        }
+        if status != 0 || result_ptr.is_null() {
+            return Err(KeychainError::PlatformError(format!(
+                "Failed to search internet password items: OSStatus {}",
+                status
+            )));
+        }
+
+        let items: CFArray<CFDictionary> =
+            unsafe { CFArray::wrap_under_create_rule(result_ptr as _) };
+
+        let mut entries = Vec::new();
+        for item in items.iter() {
+            let account = item
+                .find(unsafe { CFString::wrap_under_get_rule(kSecAttrAccount) }.as_CFType())
+                .map(|v| unsafe { CFString::wrap_under_get_rule(v as _) }.to_string());
+            let password = item
+                .find(unsafe { CFString::wrap_under_get_rule(kSecValueData) }.as_CFType())
+                .map(|v| unsafe {
+                    core_foundation::data::CFData::wrap_under_get_rule(v as _)
+                        .bytes()
+                        .to_vec()
+                });
+
+            if let (Some(account), Some(password_bytes)) = (account, password) {
+                entries.push(KeychainEntry {
+                    service: server.to_string(),
+                    account,
+                    password: String::from_utf8_lossy(&password_bytes).to_string(),
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KeychainError, Result};
+use crate::platform::{KeychainEntry, PlatformKeychain};
+use crate::{FindQuery, KeychainMetadata};
+
+#[derive(Default, Serialize, Deserialize)]
+struct Snapshot {
+    entries: HashMap<String, (String, Option<KeychainMetadata>)>,
+}
+
+fn matches_query(account: &str, metadata: &Option<KeychainMetadata>, query: &FindQuery) -> bool {
+    if let Some(prefix) = &query.account_prefix {
+        if !account.starts_with(prefix) {
+            return false;
+        }
+    }
+    if let Some(environment) = &query.environment {
+        if !account.starts_with(&format!("{}/", environment)) {
+            return false;
+        }
+    }
+    if let Some(team_id) = &query.team_id {
+        let matches = metadata
+            .as_ref()
+            .and_then(|m| m.team_id.as_ref())
+            .map_or(false, |t| t == team_id);
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(access_group) = &query.access_group {
+        let matches = metadata
+            .as_ref()
+            .and_then(|m| m.access_group.as_ref())
+            .map_or(false, |g| g == access_group);
+        if !matches {
+            return false;
+        }
+    }
+    true
+}
+
+/// First-class, fully in-memory `PlatformKeychain`, so downstream crates and
+/// CI environments with no OS keychain have a deterministic backend instead
+/// of falling back to an error like `LinuxKeychain::new`. Optionally
+/// write-through persists its full state to a single JSON file on disk after
+/// every mutation; reads are served from the in-memory map, and the file is
+/// only read once, by `with_persist_path`.
+pub struct InMemoryKeychain {
+    entries: Mutex<HashMap<String, (String, Option<KeychainMetadata>)>>,
+    persist_path: Option<PathBuf>,
+}
+
+impl Default for InMemoryKeychain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryKeychain {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            persist_path: None,
+        }
+    }
+
+    /// Loads any snapshot already at `path`, and from then on writes the
+    /// store's full state back to it after every mutation.
+    pub fn with_persist_path(path: PathBuf) -> Result<Self> {
+        let entries = if path.exists() {
+            let blob = std::fs::read_to_string(&path)
+                .map_err(|e| KeychainError::PlatformError(format!("failed to read {}: {}", path.display(), e)))?;
+            Self::parse_snapshot(&blob)?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            entries: Mutex::new(entries),
+            persist_path: Some(path),
+        })
+    }
+
+    /// Serializes the whole store to a single blob, for seeding another
+    /// instance's state in tests via `restore`.
+    pub fn snapshot(&self) -> Result<String> {
+        let entries = self.entries.lock().unwrap().clone();
+        Self::serialize_snapshot(&entries)
+    }
+
+    /// Replaces the store's contents with a blob produced by `snapshot`.
+    pub fn restore(&self, blob: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        *entries = Self::parse_snapshot(blob)?;
+        Ok(())
+    }
+
+    fn serialize_snapshot(entries: &HashMap<String, (String, Option<KeychainMetadata>)>) -> Result<String> {
+        serde_json::to_string(&Snapshot {
+            entries: entries.clone(),
+        })
+        .map_err(|e| KeychainError::InvalidData(format!("failed to serialize snapshot: {}", e)))
+    }
+
+    fn parse_snapshot(blob: &str) -> Result<HashMap<String, (String, Option<KeychainMetadata>)>> {
+        let snapshot: Snapshot = serde_json::from_str(blob)
+            .map_err(|e| KeychainError::InvalidData(format!("failed to parse snapshot: {}", e)))?;
+        Ok(snapshot.entries)
+    }
+
+    fn persist(&self, entries: &HashMap<String, (String, Option<KeychainMetadata>)>) -> Result<()> {
+        if let Some(path) = &self.persist_path {
+            let blob = Self::serialize_snapshot(entries)?;
+            std::fs::write(path, blob)
+                .map_err(|e| KeychainError::PlatformError(format!("failed to write {}: {}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PlatformKeychain for InMemoryKeychain {
+    async fn set_password(
+        &self,
+        account: &str,
+        password: &str,
+        metadata: Option<KeychainMetadata>,
+    ) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(account.to_string(), (password.to_string(), metadata));
+        self.persist(&entries)
+    }
+
+    async fn get_password(&self, account: &str) -> Result<Option<String>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.get(account).map(|(password, _)| password.clone()))
+    }
+
+    async fn delete_password(&self, account: &str) -> Result<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        let existed = entries.remove(account).is_some();
+        if existed {
+            self.persist(&entries)?;
+        }
+        Ok(existed)
+    }
+
+    async fn get_entry(&self, account: &str) -> Result<Option<KeychainEntry>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .get(account)
+            .map(|(password, metadata)| KeychainEntry {
+                service: self.get_platform_info().to_string(),
+                account: account.to_string(),
+                password: password.clone(),
+                metadata: metadata.clone(),
+            }))
+    }
+
+    async fn find_entries(&self, query: Option<FindQuery>) -> Result<Vec<KeychainEntry>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .iter()
+            .filter(|(account, (_, metadata))| match &query {
+                Some(q) => matches_query(account, metadata, q),
+                None => true,
+            })
+            .map(|(account, (password, metadata))| KeychainEntry {
+                service: self.get_platform_info().to_string(),
+                account: account.clone(),
+                password: password.clone(),
+                metadata: metadata.clone(),
+            })
+            .collect())
+    }
+
+    fn get_platform_info(&self) -> &'static str {
+        "memory"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_get_delete_roundtrip() {
+        let keychain = InMemoryKeychain::new();
+
+        keychain.set_password("acct", "secret", None).await.unwrap();
+        assert_eq!(
+            keychain.get_password("acct").await.unwrap(),
+            Some("secret".to_string())
+        );
+
+        assert!(keychain.delete_password("acct").await.unwrap());
+        assert_eq!(keychain.get_password("acct").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_entries_filters_by_prefix_and_team_id() {
+        let keychain = InMemoryKeychain::new();
+        let team_meta = KeychainMetadata {
+            team_id: Some("TEAM1".to_string()),
+            ..Default::default()
+        };
+
+        keychain
+            .set_password("app_one", "p1", Some(team_meta.clone()))
+            .await
+            .unwrap();
+        keychain.set_password("app_two", "p2", None).await.unwrap();
+        keychain.set_password("other", "p3", None).await.unwrap();
+
+        let by_prefix = keychain
+            .find_entries(Some(FindQuery {
+                account_prefix: Some("app_".to_string()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(by_prefix.len(), 2);
+
+        let by_team = keychain
+            .find_entries(Some(FindQuery {
+                team_id: Some("TEAM1".to_string()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(by_team.len(), 1);
+        assert_eq!(by_team[0].account, "app_one");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore_round_trip_state() {
+        let original = InMemoryKeychain::new();
+        original.set_password("acct", "secret", None).await.unwrap();
+        let blob = original.snapshot().unwrap();
+
+        let restored = InMemoryKeychain::new();
+        restored.restore(&blob).unwrap();
+        assert_eq!(
+            restored.get_password("acct").await.unwrap(),
+            Some("secret".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persist_path_survives_a_new_instance() {
+        let dir = std::env::temp_dir().join(format!(
+            "lpop-inmemory-keychain-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("store.json");
+
+        let first = InMemoryKeychain::with_persist_path(path.clone()).unwrap();
+        first.set_password("acct", "secret", None).await.unwrap();
+
+        let second = InMemoryKeychain::with_persist_path(path).unwrap();
+        assert_eq!(
+            second.get_password("acct").await.unwrap(),
+            Some("secret".to_string())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
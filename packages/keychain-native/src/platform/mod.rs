@@ -1,10 +1,16 @@
 #[cfg(target_os = "macos")]
 pub mod macos;
+#[cfg(target_os = "macos")]
+pub mod security_cli;
 #[cfg(target_os = "linux")]
 pub mod linux;
 #[cfg(target_os = "windows")]
 pub mod windows;
 pub mod fallback;
+pub mod encrypted;
+pub mod memory;
+pub mod remote;
+pub mod sync;
 
 #[cfg(test)]
 mod tests;
@@ -42,24 +48,245 @@ pub trait PlatformKeychain: Send + Sync {
     fn get_platform_info(&self) -> &'static str;
 }
 
-pub fn create_keychain(options: KeychainOptions) -> Result<Box<dyn PlatformKeychain>> {
-    #[cfg(target_os = "macos")]
-    {
-        Ok(Box::new(macos::MacOSKeychain::new(options)?))
+/// Lower-level keychain access keyed by an explicit `(service, account)` pair,
+/// rather than a single account scoped to one `PlatformKeychain` instance.
+/// This is what the `Keychain` napi object and its platform backends
+/// (`macos`, `linux`, `fallback`) speak.
+#[async_trait]
+pub trait KeychainAccess: Send + Sync {
+    async fn set_password(&self, service: &str, account: &str, password: &str) -> Result<()>;
+
+    async fn get_password(&self, service: &str, account: &str) -> Result<Option<String>>;
+
+    async fn delete_password(&self, service: &str, account: &str) -> Result<bool>;
+
+    async fn find_credentials(&self, service: &str) -> Result<Vec<KeychainEntry>>;
+
+    async fn find_by_account(&self, account: &str) -> Result<Vec<KeychainEntry>>;
+
+    /// Finds every entry whose `service` belongs to `server` (a bare host
+    /// like `github.com`), regardless of owner/repo/environment. Backends
+    /// that natively group credentials by host (macOS internet password
+    /// items) answer this directly; others fall back to matching `service`
+    /// against `server`, since `service` strings are themselves git remote
+    /// URLs rooted at the host.
+    async fn find_by_server(&self, server: &str) -> Result<Vec<KeychainEntry>>;
+}
+
+/// Shared fallback for backends that don't have a first-class "server"
+/// attribute to query: `service` strings are git remote URLs (or
+/// `host/owner/repo?env=...`), so matching on the host prefix approximates
+/// what macOS's `kSecAttrServer` gives natively.
+pub(crate) fn service_matches_server(service: &str, server: &str) -> bool {
+    let host_part = service
+        .split("://")
+        .last()
+        .unwrap_or(service)
+        .split('/')
+        .next()
+        .unwrap_or(service);
+    host_part == server
+}
+
+/// Adapts a `KeychainAccess` backend (keyed by an explicit `(service,
+/// account)` pair) into a `PlatformKeychain` (a single `account` scoped to
+/// one instance), for the platform backends — `MacOSKeychain`,
+/// `LinuxKeychain`, `WindowsKeychain`, `FallbackKeychain` — that only speak
+/// the lower-level trait. `service` fixes what every `PlatformKeychain` call
+/// would otherwise need to pass explicitly; `get_entry` (via `get_password`)
+/// and `find_entries` (via `find_credentials`) only see the stored password,
+/// so `metadata` is always `None` here (unlike `InMemoryKeychain` or `ObjectStoreKeychain`, which
+/// implement `PlatformKeychain` directly and do track it).
+pub(crate) struct KeychainAccessAsPlatform {
+    inner: Box<dyn KeychainAccess + Send + Sync>,
+    service: String,
+    platform_info: &'static str,
+}
+
+impl KeychainAccessAsPlatform {
+    pub(crate) fn new(
+        inner: Box<dyn KeychainAccess + Send + Sync>,
+        service: String,
+        platform_info: &'static str,
+    ) -> Self {
+        Self {
+            inner,
+            service,
+            platform_info,
+        }
     }
-    
-    #[cfg(target_os = "linux")]
-    {
-        Ok(Box::new(linux::LinuxKeychain::new(options)?))
+}
+
+#[async_trait]
+impl PlatformKeychain for KeychainAccessAsPlatform {
+    async fn set_password(
+        &self,
+        account: &str,
+        password: &str,
+        _metadata: Option<KeychainMetadata>,
+    ) -> Result<()> {
+        self.inner.set_password(&self.service, account, password).await
     }
-    
-    #[cfg(target_os = "windows")]
-    {
-        Ok(Box::new(windows::WindowsKeychain::new(options)?))
+
+    async fn get_password(&self, account: &str) -> Result<Option<String>> {
+        self.inner.get_password(&self.service, account).await
     }
-    
-    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+
+    async fn delete_password(&self, account: &str) -> Result<bool> {
+        self.inner.delete_password(&self.service, account).await
+    }
+
+    async fn get_entry(&self, account: &str) -> Result<Option<KeychainEntry>> {
+        Ok(self
+            .inner
+            .get_password(&self.service, account)
+            .await?
+            .map(|password| KeychainEntry {
+                service: self.service.clone(),
+                account: account.to_string(),
+                password,
+                metadata: None,
+            }))
+    }
+
+    async fn find_entries(&self, query: Option<FindQuery>) -> Result<Vec<KeychainEntry>> {
+        let entries = self.inner.find_credentials(&self.service).await?;
+        Ok(entries
+            .into_iter()
+            .filter(|e| match &query {
+                Some(q) => q
+                    .account_prefix
+                    .as_ref()
+                    .map_or(true, |prefix| e.account.starts_with(prefix)),
+                None => true,
+            })
+            .map(|e| KeychainEntry {
+                service: e.service,
+                account: e.account,
+                password: e.password,
+                metadata: None,
+            })
+            .collect())
+    }
+
+    fn get_platform_info(&self) -> &'static str {
+        self.platform_info
+    }
+}
+
+pub fn create_keychain_access(
+    options: Option<KeychainOptions>,
+) -> Result<Box<dyn KeychainAccess + Send + Sync>> {
+    let encryption_key = options.as_ref().and_then(|o| o.encryption_key.clone());
+    let selected_backend = options.as_ref().and_then(|o| o.backend.clone());
+
+    // The remote backend isn't platform-specific, so it's selected before
+    // falling through to the per-OS local keychain below. The local keychain
+    // stays the default; `remote` is opt-in for `lpop sync push`/`pull`.
+    if selected_backend.as_deref() == Some("remote") {
+        let remote: Box<dyn KeychainAccess + Send + Sync> = Box::new(remote::RemoteKeychain::from_env()?);
+        return match encryption_key {
+            Some(key) => Ok(Box::new(encrypted::EncryptingKeychain::new(remote, &key)?)),
+            None => Ok(remote),
+        };
+    }
+
+    let backend: Box<dyn KeychainAccess + Send + Sync> = {
+        #[cfg(target_os = "macos")]
+        {
+            let selected = options.as_ref().and_then(|o| o.backend.as_deref());
+            if selected == Some("security-cli") {
+                Box::new(security_cli::SecurityCliKeychain::new(options)?)
+            } else {
+                Box::new(macos::MacOSKeychain::new(options)?)
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Box::new(linux::LinuxKeychain::new(options)?)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            Box::new(windows::WindowsKeychain::new(options)?)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            Box::new(fallback::FallbackKeychain::new(options)?)
+        }
+    };
+
+    match encryption_key {
+        Some(key) => Ok(Box::new(encrypted::EncryptingKeychain::new(backend, &key)?)),
+        None => Ok(backend),
+    }
+}
+
+pub fn create_keychain(options: KeychainOptions) -> Result<Box<dyn PlatformKeychain>> {
+    // Captured before `options` moves into whichever branch below builds the
+    // backend, so they can still wrap the result afterwards.
+    let passphrase = options.encryption.as_ref().map(|p| p.value.clone());
+    let sync_node_id = options.sync_node_id.clone();
+    let service = options.service.clone().unwrap_or_else(|| "lpop".to_string());
+
+    // The object-store and in-memory backends aren't platform-specific, so
+    // they're selected before falling through to the per-OS local keychain
+    // below, mirroring how `create_keychain_access` selects its `"remote"`
+    // backend. `"memory"` gives embedders and CI a deterministic backend
+    // instead of falling back to an error like `LinuxKeychain::new`.
+    let backend: Box<dyn PlatformKeychain> = if options.backend.as_deref() == Some("object-store")
     {
-        Ok(Box::new(fallback::FallbackKeychain::new(options)?))
+        Box::new(remote::ObjectStoreKeychain::new(Some(options))?)
+    } else if options.backend.as_deref() == Some("memory") {
+        Box::new(memory::InMemoryKeychain::new())
+    } else {
+        // `MacOSKeychain`/`LinuxKeychain`/`WindowsKeychain`/`FallbackKeychain`
+        // only implement `KeychainAccess`, keyed by an explicit `(service,
+        // account)` pair rather than the single `account` `PlatformKeychain`
+        // expects, so they're adapted via `KeychainAccessAsPlatform` rather
+        // than boxed directly.
+        #[cfg(target_os = "macos")]
+        {
+            let access: Box<dyn KeychainAccess + Send + Sync> = Box::new(macos::MacOSKeychain::new(Some(options))?);
+            Box::new(KeychainAccessAsPlatform::new(access, service, "macos"))
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let access: Box<dyn KeychainAccess + Send + Sync> = Box::new(linux::LinuxKeychain::new(Some(options))?);
+            Box::new(KeychainAccessAsPlatform::new(access, service, "linux"))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let access: Box<dyn KeychainAccess + Send + Sync> = Box::new(windows::WindowsKeychain::new(Some(options))?);
+            Box::new(KeychainAccessAsPlatform::new(access, service, "windows"))
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            let access: Box<dyn KeychainAccess + Send + Sync> = Box::new(fallback::FallbackKeychain::new(Some(options))?);
+            Box::new(KeychainAccessAsPlatform::new(access, service, "fallback"))
+        }
+    };
+
+    let backend: Box<dyn PlatformKeychain> = match passphrase {
+        Some(passphrase) => Box::new(encrypted::EncryptingPlatformKeychain::new(
+            backend,
+            &passphrase,
+        )?),
+        None => backend,
+    };
+
+    // Wrapped last (outermost), after encryption, so the op log and
+    // checkpoints that `sync` writes through `backend` are stored as
+    // ciphertext when `encryption` is set, plaintext otherwise, without
+    // `sync` needing to know which. The ops themselves hold the plaintext
+    // values passed to `set_password`.
+    match sync_node_id {
+        Some(node_id) => Ok(Box::new(sync::SyncingPlatformKeychain::new(backend, node_id))),
+        None => Ok(backend),
     }
 }
\ No newline at end of file
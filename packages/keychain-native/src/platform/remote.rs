@@ -0,0 +1,503 @@
+//! Shares one `lpop` secret namespace across machines by implementing
+//! `KeychainAccess` against an S3/Garage-compatible object store instead of
+//! the local OS keychain, mirroring the "storage behind a trait" pattern: a
+//! single interface (`BlobStore`) exposes `blob_put`/`blob_fetch` plus a
+//! prefix-listing primitive, and any compatible store can sit behind it.
+
+use crate::error::{KeychainError, Result};
+use crate::platform::{service_matches_server, KeychainAccess, KeychainEntry, PlatformKeychain};
+use crate::{FindQuery, KeychainMetadata, KeychainOptions};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn blob_put(&self, key: &str, value: &[u8]) -> Result<()>;
+    async fn blob_fetch(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    async fn blob_delete(&self, key: &str) -> Result<bool>;
+    /// Lists every key under `prefix`.
+    async fn blob_list(&self, prefix: &str) -> Result<Vec<String>>;
+}
+
+pub struct RemoteConfig {
+    pub endpoint: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl RemoteConfig {
+    /// Reads connection details from `LPOP_SYNC_ENDPOINT`/`LPOP_SYNC_BUCKET`
+    /// (and optional `LPOP_SYNC_ACCESS_KEY`/`LPOP_SYNC_SECRET_KEY`), which is
+    /// how the `remote` backend is selected at runtime without threading
+    /// extra config through every caller.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            endpoint: std::env::var("LPOP_SYNC_ENDPOINT").ok()?,
+            bucket: std::env::var("LPOP_SYNC_BUCKET").ok()?,
+            access_key: std::env::var("LPOP_SYNC_ACCESS_KEY").unwrap_or_default(),
+            secret_key: std::env::var("LPOP_SYNC_SECRET_KEY").unwrap_or_default(),
+        })
+    }
+
+    /// Same as `from_env`, but prefers the `object_store_*` fields on
+    /// `KeychainOptions` when set, so `create_keychain`'s `"object-store"`
+    /// backend can be configured without environment variables.
+    pub fn from_options(options: &KeychainOptions) -> Option<Self> {
+        Some(Self {
+            endpoint: options
+                .object_store_endpoint
+                .clone()
+                .or_else(|| std::env::var("LPOP_SYNC_ENDPOINT").ok())?,
+            bucket: options
+                .object_store_bucket
+                .clone()
+                .or_else(|| std::env::var("LPOP_SYNC_BUCKET").ok())?,
+            access_key: options
+                .object_store_access_key
+                .clone()
+                .or_else(|| std::env::var("LPOP_SYNC_ACCESS_KEY").ok())
+                .unwrap_or_default(),
+            secret_key: options
+                .object_store_secret_key
+                .clone()
+                .or_else(|| std::env::var("LPOP_SYNC_SECRET_KEY").ok())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Talks to an S3/Garage-compatible endpoint over plain HTTP using basic
+/// auth rather than full SigV4 signing, which keeps this dependency-light;
+/// point it at a Garage instance or a thin S3-compatible proxy.
+pub struct HttpObjectStore {
+    config: RemoteConfig,
+    client: reqwest::Client,
+}
+
+impl HttpObjectStore {
+    pub fn new(config: RemoteConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.config.endpoint.trim_end_matches('/'),
+            self.config.bucket,
+            key
+        )
+    }
+}
+
+#[async_trait]
+impl BlobStore for HttpObjectStore {
+    async fn blob_put(&self, key: &str, value: &[u8]) -> Result<()> {
+        let response = self
+            .client
+            .put(self.object_url(key))
+            .basic_auth(&self.config.access_key, Some(&self.config.secret_key))
+            .body(value.to_vec())
+            .send()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("blob_put failed: {}", e)))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(KeychainError::PlatformError(format!(
+                "blob_put returned status {}",
+                response.status()
+            )))
+        }
+    }
+
+    async fn blob_fetch(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .get(self.object_url(key))
+            .basic_auth(&self.config.access_key, Some(&self.config.secret_key))
+            .send()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("blob_fetch failed: {}", e)))?;
+
+        if response.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(KeychainError::PlatformError(format!(
+                "blob_fetch returned status {}",
+                response.status()
+            )));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("blob_fetch body read failed: {}", e)))?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn blob_delete(&self, key: &str) -> Result<bool> {
+        let response = self
+            .client
+            .delete(self.object_url(key))
+            .basic_auth(&self.config.access_key, Some(&self.config.secret_key))
+            .send()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("blob_delete failed: {}", e)))?;
+
+        match response.status().as_u16() {
+            200..=299 => Ok(true),
+            404 => Ok(false),
+            status => Err(KeychainError::PlatformError(format!(
+                "blob_delete returned status {}",
+                status
+            ))),
+        }
+    }
+
+    async fn blob_list(&self, prefix: &str) -> Result<Vec<String>> {
+        // Expects the endpoint to answer `?prefix=` with one key per line;
+        // a thin proxy in front of a real S3 ListObjectsV2 XML response is
+        // assumed rather than parsing that XML here.
+        let url = format!(
+            "{}/{}?prefix={}",
+            self.config.endpoint.trim_end_matches('/'),
+            self.config.bucket,
+            prefix
+        );
+        let response = self
+            .client
+            .get(url)
+            .basic_auth(&self.config.access_key, Some(&self.config.secret_key))
+            .send()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("blob_list failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(KeychainError::PlatformError(format!(
+                "blob_list returned status {}",
+                response.status()
+            )));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("blob_list body read failed: {}", e)))?;
+        Ok(body.lines().map(str::to_string).filter(|l| !l.is_empty()).collect())
+    }
+}
+
+fn blob_key(service: &str, account: &str) -> String {
+    format!("lpop/{}/{}", service, account)
+}
+
+/// `KeychainAccess` backend for team-shared secrets, used explicitly via
+/// `lpop sync push`/`lpop sync pull` rather than as the default local store.
+pub struct RemoteKeychain {
+    store: Box<dyn BlobStore>,
+}
+
+impl RemoteKeychain {
+    pub fn new(store: Box<dyn BlobStore>) -> Self {
+        Self { store }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let config = RemoteConfig::from_env().ok_or_else(|| {
+            KeychainError::Unsupported(
+                "LPOP_SYNC_ENDPOINT/LPOP_SYNC_BUCKET not configured".to_string(),
+            )
+        })?;
+        Ok(Self::new(Box::new(HttpObjectStore::new(config))))
+    }
+}
+
+#[async_trait]
+impl KeychainAccess for RemoteKeychain {
+    async fn set_password(&self, service: &str, account: &str, password: &str) -> Result<()> {
+        self.store
+            .blob_put(&blob_key(service, account), password.as_bytes())
+            .await
+    }
+
+    async fn get_password(&self, service: &str, account: &str) -> Result<Option<String>> {
+        match self.store.blob_fetch(&blob_key(service, account)).await? {
+            Some(bytes) => Ok(Some(
+                String::from_utf8(bytes).map_err(|e| KeychainError::InvalidData(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete_password(&self, service: &str, account: &str) -> Result<bool> {
+        self.store.blob_delete(&blob_key(service, account)).await
+    }
+
+    async fn find_credentials(&self, service: &str) -> Result<Vec<KeychainEntry>> {
+        let prefix = format!("lpop/{}/", service);
+        let keys = self.store.blob_list(&prefix).await?;
+
+        let mut entries = Vec::new();
+        for key in keys {
+            let Some(account) = key.strip_prefix(&prefix) else {
+                continue;
+            };
+            if let Some(bytes) = self.store.blob_fetch(&key).await? {
+                entries.push(KeychainEntry {
+                    service: service.to_string(),
+                    account: account.to_string(),
+                    password: String::from_utf8_lossy(&bytes).to_string(),
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn find_by_account(&self, account: &str) -> Result<Vec<KeychainEntry>> {
+        let keys = self.store.blob_list("lpop/").await?;
+
+        let mut entries = Vec::new();
+        for key in keys {
+            let Some(rest) = key.strip_prefix("lpop/") else {
+                continue;
+            };
+            let Some((service, key_account)) = rest.rsplit_once('/') else {
+                continue;
+            };
+            if key_account != account {
+                continue;
+            }
+            if let Some(bytes) = self.store.blob_fetch(&key).await? {
+                entries.push(KeychainEntry {
+                    service: service.to_string(),
+                    account: account.to_string(),
+                    password: String::from_utf8_lossy(&bytes).to_string(),
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn find_by_server(&self, server: &str) -> Result<Vec<KeychainEntry>> {
+        let keys = self.store.blob_list("lpop/").await?;
+
+        let mut entries = Vec::new();
+        for key in keys {
+            let Some(rest) = key.strip_prefix("lpop/") else {
+                continue;
+            };
+            let Some((service, account)) = rest.rsplit_once('/') else {
+                continue;
+            };
+            if !service_matches_server(service, server) {
+                continue;
+            }
+            if let Some(bytes) = self.store.blob_fetch(&key).await? {
+                entries.push(KeychainEntry {
+                    service: service.to_string(),
+                    account: account.to_string(),
+                    password: String::from_utf8_lossy(&bytes).to_string(),
+                });
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// `PlatformKeychain` backend over the same object store `RemoteKeychain`
+/// speaks, for `create_keychain`'s single-account-per-instance callers
+/// rather than `KeychainAccess`'s explicit-service-per-call ones. `service`
+/// is fixed at construction (from `KeychainOptions.service`, defaulting to
+/// `"lpop"`) and each entry is stored as a serialized `KeychainEntry` —
+/// including its `KeychainMetadata` — so `find_entries` can recover
+/// everything from the object body alone.
+pub struct ObjectStoreKeychain {
+    store: Box<dyn BlobStore>,
+    service: String,
+}
+
+impl ObjectStoreKeychain {
+    pub fn new(options: Option<KeychainOptions>) -> Result<Self> {
+        let options = options.unwrap_or(KeychainOptions {
+            team_id: None,
+            access_group: None,
+            synchronizable: None,
+            backend: None,
+            encryption_key: None,
+            use_internet_password: None,
+            service: None,
+            object_store_endpoint: None,
+            object_store_bucket: None,
+            object_store_access_key: None,
+            object_store_secret_key: None,
+            encryption: None,
+            sync_node_id: None,
+        });
+
+        let config = RemoteConfig::from_options(&options).ok_or_else(|| {
+            KeychainError::Unsupported(
+                "object_store_endpoint/object_store_bucket (or LPOP_SYNC_ENDPOINT/LPOP_SYNC_BUCKET) not configured"
+                    .to_string(),
+            )
+        })?;
+
+        Ok(Self {
+            store: Box::new(HttpObjectStore::new(config)),
+            service: options.service.unwrap_or_else(|| "lpop".to_string()),
+        })
+    }
+
+    fn prefix(&self, account_prefix: Option<&str>) -> String {
+        format!("lpop/{}/{}", self.service, account_prefix.unwrap_or(""))
+    }
+}
+
+fn entry_matches_query(entry: &KeychainEntry, query: &FindQuery) -> bool {
+    if let Some(environment) = &query.environment {
+        if !entry.account.starts_with(&format!("{}/", environment)) {
+            return false;
+        }
+    }
+    if let Some(team_id) = &query.team_id {
+        let matches = entry
+            .metadata
+            .as_ref()
+            .and_then(|m| m.team_id.as_ref())
+            .map_or(false, |t| t == team_id);
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(access_group) = &query.access_group {
+        let matches = entry
+            .metadata
+            .as_ref()
+            .and_then(|m| m.access_group.as_ref())
+            .map_or(false, |g| g == access_group);
+        if !matches {
+            return false;
+        }
+    }
+    true
+}
+
+#[async_trait]
+impl PlatformKeychain for ObjectStoreKeychain {
+    async fn set_password(
+        &self,
+        account: &str,
+        password: &str,
+        metadata: Option<KeychainMetadata>,
+    ) -> Result<()> {
+        let entry = KeychainEntry {
+            service: self.service.clone(),
+            account: account.to_string(),
+            password: password.to_string(),
+            metadata,
+        };
+        let body = serde_json::to_vec(&entry)
+            .map_err(|e| KeychainError::InvalidData(format!("failed to serialize entry: {}", e)))?;
+        self.store.blob_put(&blob_key(&self.service, account), &body).await
+    }
+
+    async fn get_password(&self, account: &str) -> Result<Option<String>> {
+        Ok(self.get_entry(account).await?.map(|entry| entry.password))
+    }
+
+    async fn delete_password(&self, account: &str) -> Result<bool> {
+        self.store.blob_delete(&blob_key(&self.service, account)).await
+    }
+
+    async fn get_entry(&self, account: &str) -> Result<Option<KeychainEntry>> {
+        match self.store.blob_fetch(&blob_key(&self.service, account)).await? {
+            Some(bytes) => {
+                let entry = serde_json::from_slice(&bytes)
+                    .map_err(|e| KeychainError::InvalidData(format!("failed to parse entry: {}", e)))?;
+                Ok(Some(entry))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_entries(&self, query: Option<FindQuery>) -> Result<Vec<KeychainEntry>> {
+        let account_prefix = query.as_ref().and_then(|q| q.account_prefix.as_deref());
+        let keys = self.store.blob_list(&self.prefix(account_prefix)).await?;
+
+        let mut entries = Vec::new();
+        for key in keys {
+            let Some(bytes) = self.store.blob_fetch(&key).await? else {
+                continue;
+            };
+            let entry: KeychainEntry = serde_json::from_slice(&bytes)
+                .map_err(|e| KeychainError::InvalidData(format!("failed to parse entry: {}", e)))?;
+
+            let matches = match &query {
+                Some(q) => entry_matches_query(&entry, q),
+                None => true,
+            };
+            if matches {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn get_platform_info(&self) -> &'static str {
+        "object-store"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_key_format() {
+        assert_eq!(
+            blob_key("github.com/owner/repo?env=development", "API_KEY"),
+            "lpop/github.com/owner/repo?env=development/API_KEY"
+        );
+    }
+
+    #[test]
+    fn test_entry_matches_query_filters_by_environment_and_team() {
+        let entry = KeychainEntry {
+            service: "lpop".to_string(),
+            account: "production/API_KEY".to_string(),
+            password: "secret".to_string(),
+            metadata: Some(KeychainMetadata {
+                team_id: Some("TEAM123".to_string()),
+                ..Default::default()
+            }),
+        };
+
+        assert!(entry_matches_query(
+            &entry,
+            &FindQuery {
+                environment: Some("production".to_string()),
+                team_id: Some("TEAM123".to_string()),
+                ..Default::default()
+            }
+        ));
+        assert!(!entry_matches_query(
+            &entry,
+            &FindQuery {
+                environment: Some("staging".to_string()),
+                ..Default::default()
+            }
+        ));
+        assert!(!entry_matches_query(
+            &entry,
+            &FindQuery {
+                team_id: Some("OTHER".to_string()),
+                ..Default::default()
+            }
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::error::{KeychainError, Result};
+use crate::platform::{service_matches_server, KeychainAccess, KeychainEntry};
+use crate::KeychainOptions;
+use async_trait::async_trait;
+use std::process::Output;
+use tokio::process::Command;
+
+const SECURITY_BIN: &str = "/usr/bin/security";
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// Backend that shells out to the stable, already-trusted `/usr/bin/security`
+/// binary instead of linking against `security-framework` directly. Because
+/// every read/write goes through the same signed system binary, the ACL on
+/// each item is bound to `security`'s identity rather than to the identity of
+/// whatever debug binary last wrote it, so rebuilding/resigning `lpop` itself
+/// no longer triggers a new "allow access" prompt.
+pub struct SecurityCliKeychain {
+    synchronizable: bool,
+}
+
+impl SecurityCliKeychain {
+    pub fn new(options: Option<KeychainOptions>) -> Result<Self> {
+        let synchronizable = options
+            .and_then(|o| o.synchronizable)
+            .unwrap_or(false);
+        Ok(Self { synchronizable })
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<Output> {
+        Command::new(SECURITY_BIN)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| KeychainError::PlatformError(format!("Failed to run security: {}", e)))
+    }
+
+    fn not_found(stderr: &str) -> bool {
+        stderr.contains(&ERR_SEC_ITEM_NOT_FOUND.to_string())
+            || stderr.contains("could not be found")
+            || stderr.contains("errSecItemNotFound")
+    }
+}
+
+#[async_trait]
+impl KeychainAccess for SecurityCliKeychain {
+    async fn set_password(&self, service: &str, account: &str, password: &str) -> Result<()> {
+        // Clear any existing item first so re-running `add-generic-password`
+        // doesn't fail with "already exists".
+        let _ = self.delete_password(service, account).await;
+
+        let mut args = vec![
+            "add-generic-password",
+            "-s",
+            service,
+            "-a",
+            account,
+            "-w",
+            password,
+            "-U",
+            "-T",
+            SECURITY_BIN,
+        ];
+        if self.synchronizable {
+            args.push("-y");
+        }
+
+        let output = self.run(&args).await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(KeychainError::PlatformError(format!(
+                "security add-generic-password failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )))
+        }
+    }
+
+    async fn get_password(&self, service: &str, account: &str) -> Result<Option<String>> {
+        let output = self
+            .run(&["find-generic-password", "-s", service, "-a", account, "-w"])
+            .await?;
+
+        if output.status.success() {
+            let password = String::from_utf8_lossy(&output.stdout).trim_end().to_string();
+            Ok(Some(password))
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if Self::not_found(&stderr) {
+                Ok(None)
+            } else {
+                Err(KeychainError::PlatformError(format!(
+                    "security find-generic-password failed: {}",
+                    stderr
+                )))
+            }
+        }
+    }
+
+    async fn delete_password(&self, service: &str, account: &str) -> Result<bool> {
+        let output = self
+            .run(&["delete-generic-password", "-s", service, "-a", account])
+            .await?;
+
+        if output.status.success() {
+            Ok(true)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if Self::not_found(&stderr) {
+                Ok(false)
+            } else {
+                Err(KeychainError::PlatformError(format!(
+                    "security delete-generic-password failed: {}",
+                    stderr
+                )))
+            }
+        }
+    }
+
+    async fn find_credentials(&self, service: &str) -> Result<Vec<KeychainEntry>> {
+        let output = self.run(&["dump-keychain", "-d"]).await?;
+        if !output.status.success() {
+            return Err(KeychainError::PlatformError(format!(
+                "security dump-keychain failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        Ok(parse_dump_keychain(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|e| e.service == service)
+            .collect())
+    }
+
+    async fn find_by_account(&self, account: &str) -> Result<Vec<KeychainEntry>> {
+        let output = self.run(&["dump-keychain", "-d"]).await?;
+        if !output.status.success() {
+            return Err(KeychainError::PlatformError(format!(
+                "security dump-keychain failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        Ok(parse_dump_keychain(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|e| e.account == account)
+            .collect())
+    }
+
+    async fn find_by_server(&self, server: &str) -> Result<Vec<KeychainEntry>> {
+        let output = self.run(&["dump-keychain", "-d"]).await?;
+        if !output.status.success() {
+            return Err(KeychainError::PlatformError(format!(
+                "security dump-keychain failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        Ok(parse_dump_keychain(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|e| service_matches_server(&e.service, server))
+            .collect())
+    }
+}
+
+/// Parses the keychain-item blocks out of `security dump-keychain -d` output.
+/// Each generic password item prints attributes like `"svce"<blob>="..."`
+/// and `"acct"<blob>="..."`, followed eventually by a `password: "..."` line.
+fn parse_dump_keychain(dump: &str) -> Vec<KeychainEntry> {
+    let mut entries = Vec::new();
+    let mut service = String::new();
+    let mut account = String::new();
+
+    for line in dump.lines() {
+        let line = line.trim();
+        if let Some(value) = extract_attr(line, "svce") {
+            service = value;
+        } else if let Some(value) = extract_attr(line, "acct") {
+            account = value;
+        } else if let Some(password) = line.strip_prefix("password: ") {
+            if !service.is_empty() && !account.is_empty() {
+                entries.push(KeychainEntry {
+                    service: service.clone(),
+                    account: account.clone(),
+                    password: unquote(password),
+                });
+            }
+            service.clear();
+            account.clear();
+        }
+    }
+
+    entries
+}
+
+fn extract_attr(line: &str, name: &str) -> Option<String> {
+    let prefix = format!("\"{}\"<blob>=", name);
+    line.strip_prefix(&prefix).map(unquote)
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dump_keychain_single_entry() {
+        let dump = "\"svce\"<blob>=\"github.com/owner/repo?env=development\"\n\"acct\"<blob>=\"API_KEY\"\npassword: \"secret123\"\n";
+        let entries = parse_dump_keychain(dump);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].service, "github.com/owner/repo?env=development");
+        assert_eq!(entries[0].account, "API_KEY");
+        assert_eq!(entries[0].password, "secret123");
+    }
+
+    #[test]
+    fn test_not_found_detection() {
+        assert!(SecurityCliKeychain::not_found("security: SecKeychainSearchCopyNext: The specified item could not be found in the keychain."));
+        assert!(SecurityCliKeychain::not_found("errSecItemNotFound (-25300)"));
+        assert!(!SecurityCliKeychain::not_found("some other error"));
+    }
+}
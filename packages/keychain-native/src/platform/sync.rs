@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KeychainError, Result};
+use crate::platform::{KeychainEntry, PlatformKeychain};
+use crate::{FindQuery, KeychainMetadata};
+
+const LOG_ACCOUNT: &str = "__lpop_sync_log__";
+const CHECKPOINT_ACCOUNT: &str = "__lpop_sync_checkpoint__";
+
+/// Matches `HistoryLog`'s `KEEP_STATE_EVERY` on the CLI side: fold the log
+/// tail into a fresh checkpoint (and prune it) every this-many operations.
+const KEEP_STATE_EVERY: usize = 64;
+
+/// Orders operations from different machines without a central clock: ties
+/// on `millis` break on `node_id`, so two concurrent writes never compare
+/// equal and every device derives the same total order from the same log.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HybridTimestamp {
+    pub millis: i64,
+    pub node_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum OpKind {
+    Set {
+        password: String,
+        metadata: Option<KeychainMetadata>,
+    },
+    Delete,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub timestamp: HybridTimestamp,
+    pub account: String,
+    pub kind: OpKind,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Checkpoint {
+    state: HashMap<String, (String, Option<KeychainMetadata>)>,
+    upto: Option<HybridTimestamp>,
+}
+
+fn apply_op(state: &mut HashMap<String, (String, Option<KeychainMetadata>)>, op: &Operation) {
+    match &op.kind {
+        OpKind::Set { password, metadata } => {
+            state.insert(op.account.clone(), (password.clone(), metadata.clone()));
+        }
+        OpKind::Delete => {
+            state.remove(&op.account);
+        }
+    }
+}
+
+fn matches_query(account: &str, metadata: &Option<KeychainMetadata>, query: &FindQuery) -> bool {
+    if let Some(prefix) = &query.account_prefix {
+        if !account.starts_with(prefix) {
+            return false;
+        }
+    }
+    if let Some(environment) = &query.environment {
+        if !account.starts_with(&format!("{}/", environment)) {
+            return false;
+        }
+    }
+    if let Some(team_id) = &query.team_id {
+        let matches = metadata
+            .as_ref()
+            .and_then(|m| m.team_id.as_ref())
+            .map_or(false, |t| t == team_id);
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(access_group) = &query.access_group {
+        let matches = metadata
+            .as_ref()
+            .and_then(|m| m.access_group.as_ref())
+            .map_or(false, |g| g == access_group);
+        if !matches {
+            return false;
+        }
+    }
+    true
+}
+
+/// Wraps a `PlatformKeychain` with an append-only operation log plus
+/// periodic checkpoints, so state can be reconciled across devices without a
+/// central lock. Every `set_password`/`delete_password` is recorded as an
+/// immutable `(timestamp, account, op)` operation instead of mutating `inner`
+/// directly; the log and its checkpoints are themselves persisted through
+/// `inner`, under reserved accounts excluded from `find_entries`. Current
+/// state is always the latest checkpoint replayed forward through the
+/// operations that followed it, so `get_password`/`find_entries` never read
+/// `inner`'s per-account entries directly.
+pub struct SyncingPlatformKeychain {
+    inner: Box<dyn PlatformKeychain>,
+    node_id: String,
+}
+
+impl SyncingPlatformKeychain {
+    pub fn new(inner: Box<dyn PlatformKeychain>, node_id: impl Into<String>) -> Self {
+        Self {
+            inner,
+            node_id: node_id.into(),
+        }
+    }
+
+    fn now(&self) -> HybridTimestamp {
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        HybridTimestamp {
+            millis,
+            node_id: self.node_id.clone(),
+        }
+    }
+
+    async fn read_checkpoint(&self) -> Result<Checkpoint> {
+        match self.inner.get_password(CHECKPOINT_ACCOUNT).await? {
+            Some(serialized) => serde_json::from_str(&serialized).map_err(|e| {
+                KeychainError::InvalidParameter(format!("corrupt sync checkpoint: {e}"))
+            }),
+            None => Ok(Checkpoint::default()),
+        }
+    }
+
+    async fn write_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        let serialized = serde_json::to_string(checkpoint)
+            .map_err(|e| KeychainError::InvalidParameter(e.to_string()))?;
+        self.inner
+            .set_password(CHECKPOINT_ACCOUNT, &serialized, None)
+            .await
+    }
+
+    async fn read_tail(&self) -> Result<Vec<Operation>> {
+        match self.inner.get_password(LOG_ACCOUNT).await? {
+            Some(serialized) => serde_json::from_str(&serialized)
+                .map_err(|e| KeychainError::InvalidParameter(format!("corrupt sync log: {e}"))),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn write_tail(&self, ops: &[Operation]) -> Result<()> {
+        let serialized = serde_json::to_string(ops)
+            .map_err(|e| KeychainError::InvalidParameter(e.to_string()))?;
+        self.inner.set_password(LOG_ACCOUNT, &serialized, None).await
+    }
+
+    /// Folds `tail` into `checkpoint` and persists both once it reaches
+    /// `KEEP_STATE_EVERY`, otherwise just persists the grown tail.
+    async fn checkpoint_or_store(&self, checkpoint: Checkpoint, tail: Vec<Operation>) -> Result<()> {
+        if tail.len() >= KEEP_STATE_EVERY {
+            let mut checkpoint = checkpoint;
+            for op in &tail {
+                apply_op(&mut checkpoint.state, op);
+            }
+            checkpoint.upto = tail.last().map(|op| op.timestamp.clone());
+            self.write_checkpoint(&checkpoint).await?;
+            self.write_tail(&[]).await
+        } else {
+            self.write_tail(&tail).await
+        }
+    }
+
+    /// Latest checkpoint replayed forward through the current tail.
+    async fn state(&self) -> Result<HashMap<String, (String, Option<KeychainMetadata>)>> {
+        let mut state = self.read_checkpoint().await?.state;
+        for op in self.read_tail().await? {
+            apply_op(&mut state, &op);
+        }
+        Ok(state)
+    }
+
+    async fn record(&self, account: &str, kind: OpKind) -> Result<()> {
+        let checkpoint = self.read_checkpoint().await?;
+        let mut tail = self.read_tail().await?;
+        tail.push(Operation {
+            timestamp: self.now(),
+            account: account.to_string(),
+            kind,
+        });
+        self.checkpoint_or_store(checkpoint, tail).await
+    }
+
+    /// Ordered ops recorded for `account` since the last checkpoint.
+    pub async fn history(&self, account: &str) -> Result<Vec<Operation>> {
+        let mut ops: Vec<Operation> = self
+            .read_tail()
+            .await?
+            .into_iter()
+            .filter(|op| op.account == account)
+            .collect();
+        ops.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(ops)
+    }
+
+    /// Merges a remote device's ops into this log: anything past our
+    /// checkpoint that we don't already have is appended, the combined tail
+    /// is sorted into timestamp order, and the result is folded through the
+    /// normal checkpoint cadence so both sides converge on the same state.
+    pub async fn sync(&self, remote: &[Operation]) -> Result<()> {
+        let checkpoint = self.read_checkpoint().await?;
+        let mut tail = self.read_tail().await?;
+
+        for op in remote {
+            let is_new = checkpoint
+                .upto
+                .as_ref()
+                .map_or(true, |upto| op.timestamp > *upto)
+                && !tail.iter().any(|existing| existing.timestamp == op.timestamp);
+            if is_new {
+                tail.push(op.clone());
+            }
+        }
+        tail.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        self.checkpoint_or_store(checkpoint, tail).await
+    }
+}
+
+#[async_trait]
+impl PlatformKeychain for SyncingPlatformKeychain {
+    async fn set_password(
+        &self,
+        account: &str,
+        password: &str,
+        metadata: Option<KeychainMetadata>,
+    ) -> Result<()> {
+        self.record(
+            account,
+            OpKind::Set {
+                password: password.to_string(),
+                metadata,
+            },
+        )
+        .await
+    }
+
+    async fn get_password(&self, account: &str) -> Result<Option<String>> {
+        Ok(self.state().await?.remove(account).map(|(password, _)| password))
+    }
+
+    async fn delete_password(&self, account: &str) -> Result<bool> {
+        let existed = self.state().await?.contains_key(account);
+        if existed {
+            self.record(account, OpKind::Delete).await?;
+        }
+        Ok(existed)
+    }
+
+    async fn get_entry(&self, account: &str) -> Result<Option<KeychainEntry>> {
+        Ok(self
+            .state()
+            .await?
+            .remove(account)
+            .map(|(password, metadata)| KeychainEntry {
+                service: self.inner.get_platform_info().to_string(),
+                account: account.to_string(),
+                password,
+                metadata,
+            }))
+    }
+
+    async fn find_entries(&self, query: Option<FindQuery>) -> Result<Vec<KeychainEntry>> {
+        let service = self.inner.get_platform_info().to_string();
+        Ok(self
+            .state()
+            .await?
+            .into_iter()
+            .filter(|(account, _)| account != LOG_ACCOUNT && account != CHECKPOINT_ACCOUNT)
+            .filter(|(account, (_, metadata))| match &query {
+                Some(q) => matches_query(account, metadata, q),
+                None => true,
+            })
+            .map(|(account, (password, metadata))| KeychainEntry {
+                service: service.clone(),
+                account,
+                password,
+                metadata,
+            })
+            .collect())
+    }
+
+    fn get_platform_info(&self) -> &'static str {
+        "sync"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::tests::MockKeychain;
+
+    fn ts(millis: i64, node: &str) -> HybridTimestamp {
+        HybridTimestamp {
+            millis,
+            node_id: node.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hybrid_timestamp_orders_by_millis_then_node() {
+        assert!(ts(1, "b") > ts(1, "a"));
+        assert!(ts(2, "a") > ts(1, "z"));
+    }
+
+    #[tokio::test]
+    async fn test_set_get_delete_roundtrip_through_log() {
+        let syncing = SyncingPlatformKeychain::new(Box::new(MockKeychain::new()), "node-a");
+
+        syncing.set_password("acct", "secret", None).await.unwrap();
+        assert_eq!(
+            syncing.get_password("acct").await.unwrap(),
+            Some("secret".to_string())
+        );
+
+        assert!(syncing.delete_password("acct").await.unwrap());
+        assert_eq!(syncing.get_password("acct").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_history_returns_ops_for_account_in_order() {
+        let syncing = SyncingPlatformKeychain::new(Box::new(MockKeychain::new()), "node-a");
+
+        syncing.set_password("acct", "v1", None).await.unwrap();
+        syncing.set_password("acct", "v2", None).await.unwrap();
+        syncing.set_password("other", "v3", None).await.unwrap();
+
+        let history = syncing.history("acct").await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].timestamp <= history[1].timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_sync_merges_remote_ops_without_duplicating() {
+        let a = SyncingPlatformKeychain::new(Box::new(MockKeychain::new()), "node-a");
+        let b = SyncingPlatformKeychain::new(Box::new(MockKeychain::new()), "node-b");
+
+        b.set_password("acct", "from-b", None).await.unwrap();
+        let remote_ops = b.history("acct").await.unwrap();
+
+        a.sync(&remote_ops).await.unwrap();
+        assert_eq!(
+            a.get_password("acct").await.unwrap(),
+            Some("from-b".to_string())
+        );
+
+        // Syncing the same ops again must not create duplicate history entries.
+        a.sync(&remote_ops).await.unwrap();
+        assert_eq!(a.history("acct").await.unwrap().len(), 1);
+    }
+}
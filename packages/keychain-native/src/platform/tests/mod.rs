@@ -155,6 +155,7 @@ mod tests {
             code_signing_info: None,
             access_group: Some("com.test.app".to_string()),
             synchronizable: Some(true),
+            encryption_salt: None,
         };
 
         keychain.set_password("test_account", "test_password", Some(metadata.clone())).await.unwrap();
@@ -0,0 +1,115 @@
+//! Validates a personal access token against the API of the host a git
+//! remote points at, so `Keychain::login_remote` can confirm a secret is
+//! actually usable before it gets persisted.
+
+use crate::error::{KeychainError, Result};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct GitLabUser {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    login: String,
+}
+
+fn is_gitlab_host(host: &str) -> bool {
+    host.contains("gitlab")
+}
+
+fn api_base_for_host(host: &str) -> String {
+    if host == "github.com" {
+        "https://api.github.com".to_string()
+    } else if !is_gitlab_host(host) {
+        // GitHub Enterprise exposes its API under the same host.
+        format!("https://{}/api/v3", host)
+    } else {
+        format!("https://{}", host)
+    }
+}
+
+/// Splits a combined `username:password` (or `username:token`) credential
+/// into its parts, falling back to treating the whole string as the token
+/// when there's no `:` separator.
+pub fn split_combo_credential(credential: &str) -> (Option<String>, String) {
+    match credential.split_once(':') {
+        Some((username, secret)) => (Some(username.to_string()), secret.to_string()),
+        None => (None, credential.to_string()),
+    }
+}
+
+/// Calls the host's user API with `token` and returns the verified account
+/// name (GitLab `username` / GitHub `login`), or `KeychainError::AccessDenied`
+/// on a 401/403.
+pub async fn verify_remote_token(host: &str, token: &str) -> Result<String> {
+    let client = reqwest::Client::new();
+
+    let response = if is_gitlab_host(host) {
+        client
+            .get(format!("{}/api/v4/user", api_base_for_host(host)))
+            .header("PRIVATE-TOKEN", token)
+            .send()
+            .await
+    } else {
+        client
+            .get(format!("{}/user", api_base_for_host(host)))
+            .header("Authorization", format!("token {}", token))
+            .header("User-Agent", "lpop")
+            .send()
+            .await
+    }
+    .map_err(|e| KeychainError::PlatformError(format!("request to {} failed: {}", host, e)))?;
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        401 | 403 => return Err(KeychainError::AccessDenied),
+        status => {
+            return Err(KeychainError::PlatformError(format!(
+                "unexpected status {} from {}",
+                status, host
+            )))
+        }
+    }
+
+    if is_gitlab_host(host) {
+        let user: GitLabUser = response
+            .json()
+            .await
+            .map_err(|e| KeychainError::InvalidData(e.to_string()))?;
+        Ok(user.username)
+    } else {
+        let user: GitHubUser = response
+            .json()
+            .await
+            .map_err(|e| KeychainError::InvalidData(e.to_string()))?;
+        Ok(user.login)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_combo_credential_with_username() {
+        let (username, secret) = split_combo_credential("alice:ghp_abc123");
+        assert_eq!(username, Some("alice".to_string()));
+        assert_eq!(secret, "ghp_abc123");
+    }
+
+    #[test]
+    fn test_split_combo_credential_token_only() {
+        let (username, secret) = split_combo_credential("ghp_abc123");
+        assert_eq!(username, None);
+        assert_eq!(secret, "ghp_abc123");
+    }
+
+    #[test]
+    fn test_api_base_for_host() {
+        assert_eq!(api_base_for_host("github.com"), "https://api.github.com");
+        assert_eq!(api_base_for_host("gitlab.example.com"), "https://gitlab.example.com");
+        assert_eq!(api_base_for_host("git.internal.corp"), "https://git.internal.corp/api/v3");
+    }
+}
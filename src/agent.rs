@@ -0,0 +1,411 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use crate::secret_store::{EncryptedFileSecretStore, SecretStore};
+
+/// Request/response shapes spoken over the agent's Unix socket, each framed
+/// as a 4-byte big-endian length prefix followed by a JSON body. Follows the
+/// agent-plus-client split used by password managers like rbw: the agent
+/// holds the vault passphrase in memory so `EncryptedFileSecretStore`
+/// doesn't have to re-prompt for it on every `get_var`.
+#[derive(Serialize, Deserialize)]
+enum Request {
+    Unlock { passphrase: String },
+    Lock,
+    Get { service: String, key: String },
+    Set { service: String, key: String, value: String },
+    Delete { service: String, key: String },
+    List { service: String },
+    Clear { service: String },
+}
+
+#[derive(Serialize, Deserialize)]
+enum Response {
+    Value(Option<String>),
+    Deleted(bool),
+    Vars(HashMap<String, String>),
+    Ok,
+    /// The agent has no passphrase loaded; the client should prompt and
+    /// call `unlock` before retrying.
+    NeedsUnlock,
+    Err(String),
+}
+
+fn write_frame<T: Serialize>(stream: &mut UnixStream, value: &T) -> Result<()> {
+    let body = serde_json::to_vec(value).context("Failed to serialize agent message")?;
+    stream.write_all(&(body.len() as u32).to_be_bytes())?;
+    stream.write_all(&body)?;
+    Ok(())
+}
+
+fn read_frame<T: for<'de> Deserialize<'de>>(stream: &mut UnixStream) -> Result<T> {
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes)?;
+    let mut body = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+    stream.read_exact(&mut body)?;
+    serde_json::from_slice(&body).context("Failed to parse agent message")
+}
+
+/// Per-user socket path under `$XDG_RUNTIME_DIR` (falling back to the system
+/// temp dir), the same way e.g. ssh-agent scopes its socket per session.
+pub fn default_socket_path() -> PathBuf {
+    let base = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let user = std::env::var("USER").unwrap_or_else(|_| "lpop".to_string());
+    base.join(format!("lpop-agent-{}.sock", user))
+}
+
+struct AgentState {
+    /// `Zeroizing` so the cached passphrase (and every clone of it made to
+    /// hand off to `EncryptedFileSecretStore`) is wiped from memory on drop,
+    /// rather than lingering in the heap after the idle timeout or an
+    /// explicit `lock` clears it.
+    passphrase: Option<Zeroizing<String>>,
+    last_used: Instant,
+}
+
+/// Long-lived process that caches an unlocked vault's passphrase in memory
+/// and serves `EncryptedFileSecretStore` requests over a Unix socket, so
+/// repeated `get_var` calls against the file backend don't re-prompt for the
+/// master passphrase. `idle_timeout` zeroizes the cached passphrase once
+/// that long has passed since the last request.
+pub struct AgentServer {
+    state: Arc<Mutex<AgentState>>,
+    idle_timeout: Duration,
+}
+
+impl AgentServer {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(AgentState {
+                passphrase: None,
+                last_used: Instant::now(),
+            })),
+            idle_timeout,
+        }
+    }
+
+    /// Binds `socket_path` and serves connections until the process exits.
+    /// Removes any stale socket file left by a previous, uncleanly-stopped
+    /// agent before binding. The socket is chmod'd to 0600 right after bind
+    /// (the umask can otherwise leave it group/world-accessible), and every
+    /// connection's peer uid is checked against the socket's own owner
+    /// before servicing any request — belt-and-braces against a shared,
+    /// world-writable `$TMPDIR` when `$XDG_RUNTIME_DIR` isn't set.
+    pub fn run(self, socket_path: &std::path::Path) -> Result<()> {
+        if socket_path.exists() {
+            std::fs::remove_file(socket_path)
+                .with_context(|| format!("Failed to remove stale socket: {}", socket_path.display()))?;
+        }
+        let listener = UnixListener::bind(socket_path)
+            .with_context(|| format!("Failed to bind agent socket: {}", socket_path.display()))?;
+        std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to set permissions on agent socket: {}", socket_path.display()))?;
+        let owner_uid = std::fs::metadata(socket_path)
+            .with_context(|| format!("Failed to stat agent socket: {}", socket_path.display()))?
+            .uid();
+
+        let idle_state = self.state.clone();
+        let idle_timeout = self.idle_timeout;
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs(1));
+            let mut state = idle_state.lock().unwrap();
+            if state.passphrase.is_some() && state.last_used.elapsed() >= idle_timeout {
+                state.passphrase = None;
+            }
+        });
+
+        for stream in listener.incoming() {
+            let stream = stream.context("Failed to accept agent connection")?;
+            let state = self.state.clone();
+            std::thread::spawn(move || {
+                let _ = handle_connection(stream, state, owner_uid);
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Rejects a connection whose peer uid doesn't match the socket file's
+/// owner, so another local user who manages to open the socket path (e.g.
+/// a shared `$TMPDIR` fallback) can't issue requests even though the file
+/// permissions already bar them in the common case.
+fn handle_connection(mut stream: UnixStream, state: Arc<Mutex<AgentState>>, owner_uid: u32) -> Result<()> {
+    match stream.peer_cred() {
+        Ok(cred) if cred.uid == owner_uid => {}
+        _ => return Ok(()),
+    }
+
+    loop {
+        let request: Request = match read_frame(&mut stream) {
+            Ok(request) => request,
+            Err(_) => return Ok(()), // client disconnected
+        };
+        let response = handle_request(request, &state);
+        write_frame(&mut stream, &response)?;
+    }
+}
+
+fn handle_request(request: Request, state: &Arc<Mutex<AgentState>>) -> Response {
+    match request {
+        Request::Unlock { passphrase } => {
+            let mut state = state.lock().unwrap();
+            state.passphrase = Some(Zeroizing::new(passphrase));
+            state.last_used = Instant::now();
+            Response::Ok
+        }
+        Request::Lock => {
+            state.lock().unwrap().passphrase = None;
+            Response::Ok
+        }
+        Request::Get { service, key } => {
+            with_store(state, &service, |store| Ok(Response::Value(store.get_var(&key)?)))
+        }
+        Request::Set { service, key, value } => {
+            with_store(state, &service, |store| {
+                store.set_var(&key, &value)?;
+                Ok(Response::Ok)
+            })
+        }
+        Request::Delete { service, key } => {
+            with_store(state, &service, |store| Ok(Response::Deleted(store.delete_var(&key)?)))
+        }
+        Request::List { service } => {
+            with_store(state, &service, |store| Ok(Response::Vars(store.list_vars()?)))
+        }
+        Request::Clear { service } => {
+            with_store(state, &service, |store| {
+                store.clear_all()?;
+                Ok(Response::Ok)
+            })
+        }
+    }
+}
+
+fn with_store(
+    state: &Arc<Mutex<AgentState>>,
+    service: &str,
+    f: impl FnOnce(&EncryptedFileSecretStore) -> Result<Response>,
+) -> Response {
+    let passphrase = {
+        let mut state = state.lock().unwrap();
+        match &state.passphrase {
+            Some(passphrase) => {
+                let passphrase = passphrase.clone();
+                state.last_used = Instant::now();
+                passphrase
+            }
+            None => return Response::NeedsUnlock,
+        }
+    };
+
+    match EncryptedFileSecretStore::new(service, passphrase.to_string()) {
+        Ok(store) => f(&store).unwrap_or_else(|e| Response::Err(e.to_string())),
+        Err(e) => Response::Err(e.to_string()),
+    }
+}
+
+/// Thin client over the agent's Unix socket; `KeychainManager`'s file-backend
+/// counterpart talks to the agent exclusively through this instead of
+/// managing the passphrase itself.
+pub struct AgentClient {
+    socket_path: PathBuf,
+}
+
+impl AgentClient {
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self { socket_path }
+    }
+
+    pub fn connect_default() -> Self {
+        Self::new(default_socket_path())
+    }
+
+    fn request(&self, request: &Request) -> Result<Response> {
+        let mut stream = UnixStream::connect(&self.socket_path).with_context(|| {
+            format!(
+                "Failed to connect to lpop-agent at {} (is it running?)",
+                self.socket_path.display()
+            )
+        })?;
+        write_frame(&mut stream, request)?;
+        read_frame(&mut stream)
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        match self.request(&Request::Unlock { passphrase: passphrase.to_string() })? {
+            Response::Ok => Ok(()),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to unlock"),
+        }
+    }
+
+    pub fn lock(&self) -> Result<()> {
+        match self.request(&Request::Lock)? {
+            Response::Ok => Ok(()),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to lock"),
+        }
+    }
+}
+
+/// `SecretStore` that forwards every call to a running `lpop-agent`,
+/// prompting the caller to run `lpop agent unlock` instead of the
+/// passphrase itself when the agent has no key loaded.
+pub struct AgentSecretStore {
+    client: AgentClient,
+    service: String,
+}
+
+impl AgentSecretStore {
+    pub fn new(service: String) -> Self {
+        Self {
+            client: AgentClient::connect_default(),
+            service,
+        }
+    }
+
+    fn request(&self, request: Request) -> Result<Response> {
+        match self.client.request(&request)? {
+            Response::NeedsUnlock => {
+                bail!("lpop-agent has no passphrase loaded; run `lpop agent unlock` first")
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+impl SecretStore for AgentSecretStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        match self.request(Request::Get { service: self.service.clone(), key: key.to_string() })? {
+            Response::Value(value) => Ok(value),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to get"),
+        }
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        match self.request(Request::Set {
+            service: self.service.clone(),
+            key: key.to_string(),
+            value: value.to_string(),
+        })? {
+            Response::Ok => Ok(()),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to set"),
+        }
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        match self.request(Request::Delete { service: self.service.clone(), key: key.to_string() })? {
+            Response::Deleted(existed) => Ok(existed),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to delete"),
+        }
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        match self.request(Request::Clear { service: self.service.clone() })? {
+            Response::Ok => Ok(()),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to clear"),
+        }
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        match self.request(Request::List { service: self.service.clone() })? {
+            Response::Vars(vars) => Ok(vars),
+            Response::Err(e) => bail!(e),
+            _ => bail!("Unexpected agent response to list"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlock_then_get_set_round_trips_through_socket() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("LPOP_STORE_DIR", dir.path());
+        let socket_path = dir.path().join("agent.sock");
+
+        let server = AgentServer::new(Duration::from_secs(60));
+        let server_socket = socket_path.clone();
+        std::thread::spawn(move || server.run(&server_socket));
+        std::thread::sleep(Duration::from_millis(100));
+
+        let client = AgentClient::new(socket_path);
+        client.unlock("correct horse").unwrap();
+
+        let store = AgentSecretStore { client, service: "svc".to_string() };
+        store.set_var("KEY", "value").unwrap();
+        assert_eq!(store.get_var("KEY").unwrap(), Some("value".to_string()));
+
+        std::env::remove_var("LPOP_STORE_DIR");
+    }
+
+    #[test]
+    fn test_get_without_unlock_reports_needs_unlock() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("LPOP_STORE_DIR", dir.path());
+        let socket_path = dir.path().join("agent.sock");
+
+        let server = AgentServer::new(Duration::from_secs(60));
+        let server_socket = socket_path.clone();
+        std::thread::spawn(move || server.run(&server_socket));
+        std::thread::sleep(Duration::from_millis(100));
+
+        let client = AgentClient::new(socket_path);
+        let store = AgentSecretStore { client, service: "svc".to_string() };
+        assert!(store.get_var("KEY").is_err());
+
+        std::env::remove_var("LPOP_STORE_DIR");
+    }
+
+    #[test]
+    fn test_socket_is_chmoded_to_owner_only() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket_path = dir.path().join("agent.sock");
+
+        let server = AgentServer::new(Duration::from_secs(60));
+        let server_socket = socket_path.clone();
+        std::thread::spawn(move || server.run(&server_socket));
+        std::thread::sleep(Duration::from_millis(100));
+
+        let mode = std::fs::metadata(&socket_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_idle_timeout_locks_the_cached_passphrase() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("LPOP_STORE_DIR", dir.path());
+        let socket_path = dir.path().join("agent.sock");
+
+        let server = AgentServer::new(Duration::from_millis(200));
+        let server_socket = socket_path.clone();
+        std::thread::spawn(move || server.run(&server_socket));
+        std::thread::sleep(Duration::from_millis(100));
+
+        let client = AgentClient::new(socket_path);
+        client.unlock("correct horse").unwrap();
+        std::thread::sleep(Duration::from_millis(1300));
+
+        let store = AgentSecretStore { client, service: "svc".to_string() };
+        assert!(store.get_var("KEY").is_err());
+
+        std::env::remove_var("LPOP_STORE_DIR");
+    }
+}
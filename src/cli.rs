@@ -4,9 +4,14 @@ use colored::*;
 use std::path::{Path, PathBuf};
 
 use crate::{
-    env_parser::EnvFileParser,
+    agent,
+    env_parser::{EnvDiff, EnvFileParser},
     git_resolver::GitPathResolver,
-    keychain::KeychainManager,
+    history::{HistoryLog, OpKind},
+    keychain::{FindQuery, KeychainManager},
+    secret_store,
+    sync,
+    vault,
 };
 
 #[derive(Parser)]
@@ -23,6 +28,13 @@ pub struct Cli {
     /// Environment name
     #[arg(short, long, default_value = "development")]
     pub env: String,
+
+    /// Which storage backend to use: keychain, memory, file, agent, op, pass,
+    /// security, or helper. Defaults to keychain, or to security in macOS
+    /// debug builds. The memory and file backends let `lpop` run on hosts with no system
+    /// keychain, e.g. headless Linux servers or CI.
+    #[arg(long, env = "LPOP_BACKEND")]
+    pub backend: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -78,14 +90,275 @@ pub enum Commands {
         #[arg(short, long)]
         env: Option<String>,
     },
+
+    /// Export/import an encrypted, portable vault file
+    Vault {
+        #[command(subcommand)]
+        action: VaultAction,
+    },
+
+    /// Act as a `git-credential-lpop` helper (see `git help credential`)
+    GitCredential {
+        /// get, store, or erase
+        operation: String,
+    },
+
+    /// Act as a generic JSON credential-process helper over stdin/stdout
+    CredentialProcess,
+
+    /// Show the operation history for an environment
+    History {
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+
+    /// Roll back an environment's variables to a prior point in time
+    Rollback {
+        /// Timestamp to restore state as of, as printed by `lpop history`
+        timestamp: String,
+
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+
+    /// Export all variables for an environment into a single encrypted,
+    /// portable bundle file (Argon2id + AES-256-GCM)
+    Export {
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Path to write the bundle to (default: `<env>.lpop`)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Import variables from an encrypted bundle produced by `lpop export`
+    Import {
+        /// Path to the bundle file
+        file: PathBuf,
+
+        /// Environment name to import into
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+
+    /// Push/pull encrypted environments to/from the git remote this repo
+    /// already uses for code, so a team shares secrets the same way
+    Sync {
+        #[command(subcommand)]
+        action: SyncAction,
+    },
+
+    /// Reconcile an environment's stored variables to match a .env file
+    Apply {
+        /// Path to the .env file describing desired state
+        file: PathBuf,
+
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Print the planned changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also delete stored keys that are absent from the file
+        #[arg(long)]
+        prune: bool,
+    },
+
+    /// Watch a .env file and keep an environment's stored variables in sync
+    /// with it as it's edited, until interrupted
+    Watch {
+        /// Path to the .env file to watch
+        file: PathBuf,
+
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Also delete stored keys that are absent from the file
+        #[arg(long)]
+        prune: bool,
+    },
+
+    /// Run or control the background agent that caches an unlocked `file`
+    /// backend vault's passphrase, so it isn't re-prompted on every call
+    Agent {
+        #[command(subcommand)]
+        action: AgentAction,
+    },
+
+    /// Migrate a plaintext .env file's variables into the keystore
+    ImportEnv {
+        /// Path to the .env file
+        file: PathBuf,
+
+        /// Environment name to import into
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Keep the stored value for any variable that already exists
+        #[arg(long, conflicts_with = "overwrite")]
+        skip: bool,
+
+        /// Replace the stored value for any variable that already exists
+        #[arg(long, conflicts_with = "skip")]
+        overwrite: bool,
+
+        /// Delete the plaintext file once its variables are in the keystore
+        #[arg(long)]
+        delete_after: bool,
+    },
+
+    /// Write an environment's stored variables back out to a plaintext .env file
+    ExportEnv {
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Path to write the .env file to (default: `.env`, or `.env.<environment>`)
+        out: Option<PathBuf>,
+
+        /// Print `export KEY=value` lines to stdout instead of writing a file
+        #[arg(long)]
+        stdout: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AgentAction {
+    /// Start the agent in the foreground, listening on its Unix socket
+    Start {
+        /// Seconds of inactivity after which the cached passphrase is dropped
+        #[arg(long, default_value_t = 600)]
+        idle_timeout_secs: u64,
+    },
+
+    /// Prompt for the vault passphrase and load it into a running agent
+    Unlock,
+
+    /// Drop the agent's cached passphrase
+    Lock,
+}
+
+#[derive(Subcommand)]
+pub enum SyncAction {
+    /// Seal the current environment's variables and push them to the remote
+    Push {
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Remote to push to (defaults to `origin`)
+        #[arg(short, long)]
+        remote: Option<String>,
+    },
+
+    /// Fetch and decrypt an environment previously pushed by a teammate
+    Pull {
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+
+        /// Remote to pull from (defaults to `origin`)
+        #[arg(short, long)]
+        remote: Option<String>,
+    },
 }
 
-pub fn handle_get(key: Option<String>, env: Option<String>, _all: bool) -> Result<()> {
+#[derive(Subcommand)]
+pub enum VaultAction {
+    /// Export all variables for an environment into an encrypted vault file
+    Export {
+        /// Path to write the vault file to
+        file: PathBuf,
+
+        /// Environment name
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+
+    /// Import variables from an encrypted vault file
+    Import {
+        /// Path to the vault file
+        file: PathBuf,
+
+        /// Environment name to import into
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+}
+
+/// `KeychainManager::find_entries` enumerates across every environment for a
+/// repo by shelling out to `security dump-keychain`, so it only ever sees
+/// what's in the real OS keychain — it has no idea what's stored under
+/// `--backend memory/file/agent/op/pass/security/helper`, and even on the
+/// `"keychain"` backend it only actually works on macOS: the `keyring` crate
+/// has no cross-service enumeration API on Linux/Windows, so `find_entries`
+/// is a hardcoded no-op there. Rather than silently reporting "no variables
+/// found" for a case it can't see into, callers that need repo-wide
+/// enumeration check this first and fail loudly.
+fn require_keychain_backend(backend: Option<&str>, action: &str) -> Result<()> {
+    let resolved = secret_store::resolved_backend_name(backend);
+    if resolved != "keychain" {
+        eprintln!(
+            "{} {} requires the keychain backend to enumerate across environments (current backend: {})",
+            "✗".red(),
+            action,
+            resolved
+        );
+        std::process::exit(1);
+    }
+    if !cfg!(target_os = "macos") {
+        eprintln!(
+            "{} {} requires the keychain backend's cross-environment enumeration, which is only implemented on macOS",
+            "✗".red(),
+            action
+        );
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+pub fn handle_get(
+    key: Option<String>,
+    env: Option<String>,
+    all: bool,
+    backend: Option<String>,
+) -> Result<()> {
     let git_resolver = GitPathResolver::new(None);
     let env = env.unwrap_or_else(|| "development".to_string());
     let service_name = git_resolver.generate_service_name(&env);
-    let keychain = KeychainManager::new(service_name.clone());
-    
+    let repo = GitPathResolver::extract_repo_from_service(&service_name).to_string();
+
+    if all {
+        // Get all variables across every environment for this repo.
+        // `find_entries` enumerates by shelling out to `security
+        // dump-keychain`, so it only knows about the real OS keychain, not
+        // whatever `--backend`/`LPOP_BACKEND` resolves to.
+        require_keychain_backend(backend.as_deref(), "--all")?;
+        let entries = KeychainManager::find_entries(&repo, &FindQuery::default())?;
+        println!("{} {}\n", "Repository:".bright_blue(), repo);
+
+        if entries.is_empty() {
+            println!("{}", "No variables found across any environment".yellow());
+        } else {
+            let mut entries = entries;
+            entries.sort();
+            for (environment, key, value) in entries {
+                println!("[{}] {} = {}", environment.cyan(), key, value);
+            }
+        }
+
+        return Ok(());
+    }
+
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+
     if let Some(key) = key {
         // Get single variable
         match keychain.get_var(&key)? {
@@ -93,22 +366,28 @@ pub fn handle_get(key: Option<String>, env: Option<String>, _all: bool) -> Resul
                 println!("{}", value);
             }
             None => {
-                eprintln!("{} Variable '{}' not found in {} environment", 
+                eprintln!("{} Variable '{}' not found in {} environment",
                     "✗".red(), key, env);
                 std::process::exit(1);
             }
         }
     } else {
-        // Get all variables
-        println!("{} {}", "Repository:".bright_blue(), 
-            GitPathResolver::extract_repo_from_service(&service_name));
+        // Get all variables for this environment
+        let vars = keychain.list_vars()?;
+        println!("{} {}", "Repository:".bright_blue(), repo);
         println!("{} {}\n", "Environment:".bright_blue(), env);
-        
-        // Note: This is a limitation of the keyring crate
-        // In real implementation, we'd need to track keys separately
-        println!("{}", "Note: Listing all variables not yet implemented".yellow());
+
+        if vars.is_empty() {
+            println!("{}", "No variables set".yellow());
+        } else {
+            let mut keys: Vec<&String> = vars.keys().collect();
+            keys.sort();
+            for key in keys {
+                println!("{} = {}", key, vars[key]);
+            }
+        }
     }
-    
+
     Ok(())
 }
 
@@ -117,17 +396,22 @@ pub fn handle_set(
     value: Option<String>,
     env: Option<String>,
     file: Option<PathBuf>,
+    backend: Option<String>,
 ) -> Result<()> {
     let git_resolver = GitPathResolver::new(None);
     let env = env.unwrap_or_else(|| "development".to_string());
     let service_name = git_resolver.generate_service_name(&env);
-    let keychain = KeychainManager::new(service_name);
-    
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+    let history = HistoryLog::new(service_name);
+
     if let Some(file_path) = file {
         // Set from file
         let vars = EnvFileParser::parse_file(&file_path)?;
         keychain.set_vars(vars.clone())?;
-        
+        for (key, value) in &vars {
+            history.record(OpKind::Set, key, Some(value.clone()))?;
+        }
+
         println!("{} Set {} variables from {} in {} environment",
             "✓".green(),
             vars.len(),
@@ -137,6 +421,7 @@ pub fn handle_set(
     } else if let (Some(key), Some(value)) = (key, value) {
         // Set single variable
         keychain.set_var(&key, &value)?;
+        history.record(OpKind::Set, &key, Some(value))?;
         println!("{} Set {} in {} environment", "✓".green(), key, env);
     } else {
         eprintln!("{} Must provide either key/value or --file", "✗".red());
@@ -146,17 +431,28 @@ pub fn handle_set(
     Ok(())
 }
 
-pub fn handle_delete(key: Option<String>, env: Option<String>, all: bool) -> Result<()> {
+pub fn handle_delete(
+    key: Option<String>,
+    env: Option<String>,
+    all: bool,
+    backend: Option<String>,
+) -> Result<()> {
     let git_resolver = GitPathResolver::new(None);
     let env = env.unwrap_or_else(|| "development".to_string());
     let service_name = git_resolver.generate_service_name(&env);
-    let keychain = KeychainManager::new(service_name);
-    
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+    let history = HistoryLog::new(service_name);
+
     if all {
+        let vars = keychain.list_vars()?;
         keychain.clear_all()?;
+        for key in vars.keys() {
+            history.record(OpKind::Delete, key, None)?;
+        }
         println!("{} Deleted all variables in {} environment", "✓".green(), env);
     } else if let Some(key) = key {
         if keychain.delete_var(&key)? {
+            history.record(OpKind::Delete, &key, None)?;
             println!("{} Deleted {} from {} environment", "✓".green(), key, env);
         } else {
             eprintln!("{} Variable '{}' not found in {} environment",
@@ -171,21 +467,561 @@ pub fn handle_delete(key: Option<String>, env: Option<String>, all: bool) -> Res
     Ok(())
 }
 
-pub fn handle_list(_env: Option<String>) -> Result<()> {
-    let _git_resolver = GitPathResolver::new(None);
-    
-    // This would list all environments, but requires tracking them
-    println!("{}", "Listing environments not yet implemented".yellow());
-    
+pub fn handle_list(env: Option<String>, backend: Option<String>) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+
+    match env {
+        Some(env) => {
+            // List the variable keys stored for one environment.
+            let service_name = git_resolver.generate_service_name(&env);
+            let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+
+            let vars = keychain.list_vars()?;
+            println!("{} {}", "Repository:".bright_blue(), GitPathResolver::extract_repo_from_service(&service_name));
+            println!("{} {}\n", "Environment:".bright_blue(), env);
+
+            if vars.is_empty() {
+                println!("{}", "No variables set".yellow());
+            } else {
+                let mut keys: Vec<&String> = vars.keys().collect();
+                keys.sort();
+                for key in keys {
+                    println!("{}", key);
+                }
+            }
+        }
+        None => {
+            // List every environment this repo has stored variables in.
+            // Same `find_entries`-is-keychain-only caveat as `handle_get`'s
+            // `--all` branch.
+            require_keychain_backend(backend.as_deref(), "listing environments")?;
+            let service_name = git_resolver.generate_service_name("development");
+            let repo = GitPathResolver::extract_repo_from_service(&service_name).to_string();
+            let entries = KeychainManager::find_entries(&repo, &FindQuery::default())?;
+
+            println!("{} {}\n", "Repository:".bright_blue(), repo);
+
+            let environments: std::collections::BTreeSet<String> =
+                entries.into_iter().map(|(environment, _, _)| environment).collect();
+
+            if environments.is_empty() {
+                println!("{}", "No environments found".yellow());
+            } else {
+                for environment in environments {
+                    println!("{}", environment);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_vault(action: VaultAction, backend: Option<String>) -> Result<()> {
+    match action {
+        VaultAction::Export { file, env } => {
+            let git_resolver = GitPathResolver::new(None);
+            let env = env.unwrap_or_else(|| "development".to_string());
+            let service_name = git_resolver.generate_service_name(&env);
+            let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+            let vars = keychain.list_vars()?;
+            let passphrase = rpassword::prompt_password("Vault passphrase: ")?;
+            vault::export_vars(&vars, &passphrase, &file)?;
+
+            println!(
+                "{} Exported {} variables from {} environment to {}",
+                "✓".green(),
+                vars.len(),
+                env,
+                file.display()
+            );
+        }
+        VaultAction::Import { file, env } => {
+            let git_resolver = GitPathResolver::new(None);
+            let env = env.unwrap_or_else(|| "development".to_string());
+            let service_name = git_resolver.generate_service_name(&env);
+            let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+            let passphrase = rpassword::prompt_password("Vault passphrase: ")?;
+            let vars = vault::import_vars(&passphrase, &file)?;
+            let count = vars.len();
+            keychain.set_vars(vars)?;
+
+            println!(
+                "{} Imported {} variables from {} into {} environment",
+                "✓".green(),
+                count,
+                file.display(),
+                env
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_history(env: Option<String>) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let history = HistoryLog::new(service_name);
+
+    let ops = history.list_ops()?;
+    if ops.is_empty() {
+        println!("{}", "No recorded history since the last checkpoint".yellow());
+        return Ok(());
+    }
+
+    for op in ops {
+        match op.kind {
+            OpKind::Set => println!(
+                "{}  set     {} = {}",
+                op.timestamp,
+                op.key,
+                op.value.unwrap_or_default()
+            ),
+            OpKind::Delete => println!("{}  delete  {}", op.timestamp, op.key),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_rollback(timestamp: String, env: Option<String>, backend: Option<String>) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+    let history = HistoryLog::new(service_name);
+
+    let target_state = history.state_as_of(&timestamp)?;
+    let current_vars = keychain.list_vars()?;
+
+    for key in current_vars.keys() {
+        if !target_state.contains_key(key) {
+            keychain.delete_var(key)?;
+            history.record(OpKind::Delete, key, None)?;
+        }
+    }
+
+    for (key, value) in &target_state {
+        if current_vars.get(key) != Some(value) {
+            keychain.set_var(key, value)?;
+            history.record(OpKind::Set, key, Some(value.clone()))?;
+        }
+    }
+
+    println!(
+        "{} Rolled back {} environment to state as of {}",
+        "✓".green(),
+        env,
+        timestamp
+    );
+    Ok(())
+}
+
+pub fn handle_export(env: Option<String>, out: Option<PathBuf>, backend: Option<String>) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+    let vars = keychain.list_vars()?;
+    let path = out.unwrap_or_else(|| PathBuf::from(format!("{}.lpop", env)));
+    let passphrase = rpassword::prompt_password("Export passphrase: ")?;
+    vault::export_bundle(&vars, &passphrase, &path)?;
+
+    println!(
+        "{} Exported {} variables from {} environment to {}",
+        "✓".green(),
+        vars.len(),
+        env,
+        path.display()
+    );
+    Ok(())
+}
+
+pub fn handle_import(file: PathBuf, env: Option<String>, backend: Option<String>) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+    let passphrase = rpassword::prompt_password("Import passphrase: ")?;
+    let vars = vault::import_bundle(&passphrase, &file)?;
+    let count = vars.len();
+    keychain.set_vars(vars)?;
+
+    println!(
+        "{} Imported {} variables from {} into {} environment",
+        "✓".green(),
+        count,
+        file.display(),
+        env
+    );
+    Ok(())
+}
+
+pub fn handle_apply(
+    file: PathBuf,
+    env: Option<String>,
+    dry_run: bool,
+    prune: bool,
+    backend: Option<String>,
+) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+    let history = HistoryLog::new(service_name);
+
+    let desired = EnvFileParser::parse_file(&file)?;
+    let current = keychain.list_vars()?;
+
+    let mut additions: Vec<(&String, &String)> = Vec::new();
+    let mut updates: Vec<(&String, &String)> = Vec::new();
+    for (key, value) in &desired {
+        match current.get(key) {
+            None => additions.push((key, value)),
+            Some(existing) if existing != value => updates.push((key, value)),
+            Some(_) => {}
+        }
+    }
+
+    let mut deletions: Vec<&String> = Vec::new();
+    if prune {
+        for key in current.keys() {
+            if !desired.contains_key(key) {
+                deletions.push(key);
+            }
+        }
+    }
+
+    additions.sort_by_key(|(k, _)| k.clone());
+    updates.sort_by_key(|(k, _)| k.clone());
+    deletions.sort();
+
+    if additions.is_empty() && updates.is_empty() && deletions.is_empty() {
+        println!("{} {} environment already matches {}", "✓".green(), env, file.display());
+        return Ok(());
+    }
+
+    if dry_run {
+        for (key, value) in &additions {
+            println!("{} add    {} = {}", "+".green(), key, value);
+        }
+        for (key, value) in &updates {
+            println!("{} update {} = {}", "~".yellow(), key, value);
+        }
+        for key in &deletions {
+            println!("{} delete {}", "-".red(), key);
+        }
+        println!(
+            "{} Dry run: {} to add, {} to update, {} to delete",
+            "✓".green(),
+            additions.len(),
+            updates.len(),
+            deletions.len()
+        );
+        return Ok(());
+    }
+
+    for (key, value) in &additions {
+        keychain.set_var(key, value)?;
+        history.record(OpKind::Set, key, Some((*value).clone()))?;
+    }
+    for (key, value) in &updates {
+        keychain.set_var(key, value)?;
+        history.record(OpKind::Set, key, Some((*value).clone()))?;
+    }
+    for key in &deletions {
+        keychain.delete_var(key)?;
+        history.record(OpKind::Delete, key, None)?;
+    }
+
+    println!(
+        "{} Applied {}: {} added, {} updated, {} deleted in {} environment",
+        "✓".green(),
+        file.display(),
+        additions.len(),
+        updates.len(),
+        deletions.len(),
+        env
+    );
+
+    Ok(())
+}
+
+/// Applies one `EnvDiff` to `keychain`, recording each change through
+/// `history` and printing it the same way `handle_apply`'s non-dry-run path
+/// does, so `watch` output reads like a live stream of `apply` runs.
+fn apply_diff(
+    diff: EnvDiff,
+    keychain: &dyn secret_store::SecretStore,
+    history: &HistoryLog,
+    prune: bool,
+) {
+    let mut added: Vec<_> = diff.added.into_iter().collect();
+    added.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (key, value) in added {
+        match keychain.set_var(&key, &value) {
+            Ok(()) => {
+                let _ = history.record(OpKind::Set, &key, Some(value.clone()));
+                println!("{} add    {} = {}", "+".green(), key, value);
+            }
+            Err(e) => eprintln!("{} Failed to set {}: {}", "✗".red(), key, e),
+        }
+    }
+
+    let mut changed: Vec<_> = diff.changed.into_iter().collect();
+    changed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (key, (_, new_value)) in changed {
+        match keychain.set_var(&key, &new_value) {
+            Ok(()) => {
+                let _ = history.record(OpKind::Set, &key, Some(new_value.clone()));
+                println!("{} update {} = {}", "~".yellow(), key, new_value);
+            }
+            Err(e) => eprintln!("{} Failed to update {}: {}", "✗".red(), key, e),
+        }
+    }
+
+    if prune {
+        let mut removed = diff.removed;
+        removed.sort();
+        for key in removed {
+            match keychain.delete_var(&key) {
+                Ok(_) => {
+                    let _ = history.record(OpKind::Delete, &key, None);
+                    println!("{} delete {}", "-".red(), key);
+                }
+                Err(e) => eprintln!("{} Failed to delete {}: {}", "✗".red(), key, e),
+            }
+        }
+    }
+}
+
+/// Watches `file` and applies every change to the `env` environment as it
+/// happens, blocking until the process is interrupted (e.g. Ctrl-C).
+pub fn handle_watch(file: PathBuf, env: Option<String>, prune: bool, backend: Option<String>) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+    let history = HistoryLog::new(service_name);
+
+    println!(
+        "{} Watching {} for changes to the {} environment (Ctrl-C to stop)...",
+        "✓".green(),
+        file.display(),
+        env
+    );
+
+    let (_watcher, diffs) = EnvFileParser::watch(file, std::time::Duration::from_millis(300))?;
+    for diff in diffs {
+        apply_diff(diff, keychain.as_ref(), &history, prune);
+    }
+
     Ok(())
 }
 
-pub fn handle_smart_command(input: String) -> Result<()> {
+pub fn handle_agent(action: AgentAction) -> Result<()> {
+    match action {
+        AgentAction::Start { idle_timeout_secs } => {
+            let socket_path = agent::default_socket_path();
+            println!("{} lpop-agent listening on {}", "✓".green(), socket_path.display());
+            agent::AgentServer::new(std::time::Duration::from_secs(idle_timeout_secs)).run(&socket_path)
+        }
+        AgentAction::Unlock => {
+            let passphrase = rpassword::prompt_password("Vault passphrase: ")?;
+            agent::AgentClient::connect_default().unlock(&passphrase)?;
+            println!("{} lpop-agent unlocked", "✓".green());
+            Ok(())
+        }
+        AgentAction::Lock => {
+            agent::AgentClient::connect_default().lock()?;
+            println!("{} lpop-agent locked", "✓".green());
+            Ok(())
+        }
+    }
+}
+
+pub fn handle_sync(action: SyncAction, backend: Option<String>) -> Result<()> {
+    match action {
+        SyncAction::Push { env, remote } => {
+            let git_resolver = GitPathResolver::new(None);
+            let env = env.unwrap_or_else(|| "development".to_string());
+            let service_name = git_resolver.generate_service_name(&env);
+            let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+            let vars = keychain.list_vars()?;
+            let passphrase = rpassword::prompt_password("Sync passphrase: ")?;
+            sync::push(None, remote, &env, &passphrase, &vars)?;
+
+            println!(
+                "{} Pushed {} variables for {} environment to the sync ref",
+                "✓".green(),
+                vars.len(),
+                env
+            );
+        }
+        SyncAction::Pull { env, remote } => {
+            let git_resolver = GitPathResolver::new(None);
+            let env = env.unwrap_or_else(|| "development".to_string());
+            let service_name = git_resolver.generate_service_name(&env);
+            let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+            let passphrase = rpassword::prompt_password("Sync passphrase: ")?;
+            match sync::pull(None, remote, &env, &passphrase)? {
+                Some(vars) => {
+                    let count = vars.len();
+                    keychain.set_vars(vars)?;
+                    println!(
+                        "{} Pulled {} variables for {} environment",
+                        "✓".green(),
+                        count,
+                        env
+                    );
+                }
+                None => {
+                    eprintln!(
+                        "{} No synced bundle found for {} environment",
+                        "✗".red(),
+                        env
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Migrates a plaintext `.env` file into the keystore. Unlike `Import`
+/// (which reads an encrypted bundle produced by `lpop export`), this reads
+/// `.env`-format plaintext, the same format `Apply`/`Watch`/`Set --file`
+/// already parse via `EnvFileParser`. Keys that already exist in the target
+/// environment are a conflict: run again with `--skip` or `--overwrite` to
+/// resolve them, so a second run of the same file is never ambiguous.
+pub fn handle_import_env(
+    file: PathBuf,
+    env: Option<String>,
+    skip: bool,
+    overwrite: bool,
+    delete_after: bool,
+    backend: Option<String>,
+) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name.clone(), backend.as_deref())?;
+    let history = HistoryLog::new(service_name);
+
+    let parsed = EnvFileParser::parse_file(&file)?;
+    let existing = keychain.list_vars()?;
+
+    let mut conflicts: Vec<&String> = parsed.keys().filter(|k| existing.contains_key(*k)).collect();
+    conflicts.sort();
+    if !conflicts.is_empty() && !skip && !overwrite {
+        eprintln!(
+            "{} {} variable(s) already exist in {} environment: {}",
+            "✗".red(),
+            conflicts.len(),
+            env,
+            conflicts.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+        );
+        eprintln!("Re-run with --skip to keep the stored values or --overwrite to replace them");
+        std::process::exit(1);
+    }
+
+    let mut keys: Vec<&String> = parsed.keys().collect();
+    keys.sort();
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    for key in keys {
+        if skip && existing.contains_key(key) {
+            skipped += 1;
+            continue;
+        }
+        let value = &parsed[key];
+        keychain.set_var(key, value)?;
+        history.record(OpKind::Set, key, Some(value.clone()))?;
+        imported += 1;
+    }
+
+    println!(
+        "{} Imported {} variable(s) ({} skipped) from {} into {} environment",
+        "✓".green(),
+        imported,
+        skipped,
+        file.display(),
+        env
+    );
+
+    if delete_after {
+        std::fs::remove_file(&file)?;
+        println!("{} Removed {} now that its variables are in the keystore", "✓".green(), file.display());
+    }
+
+    Ok(())
+}
+
+/// Quotes a value for the `export KEY=value` lines `ExportEnv --stdout`
+/// prints, so the output can be fed straight into `eval "$(lpop export-env
+/// --stdout)"` even when a value contains spaces or shell metacharacters.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+pub fn handle_export_env(
+    env: Option<String>,
+    out: Option<PathBuf>,
+    stdout: bool,
+    backend: Option<String>,
+) -> Result<()> {
+    let git_resolver = GitPathResolver::new(None);
+    let env = env.unwrap_or_else(|| "development".to_string());
+    let service_name = git_resolver.generate_service_name(&env);
+    let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+    let vars = keychain.list_vars()?;
+    let mut keys: Vec<&String> = vars.keys().collect();
+    keys.sort();
+
+    if stdout {
+        for key in keys {
+            println!("export {}={}", key, shell_quote(&vars[key]));
+        }
+        return Ok(());
+    }
+
+    let path = out.unwrap_or_else(|| {
+        if env == "development" {
+            PathBuf::from(".env")
+        } else {
+            PathBuf::from(format!(".env.{}", env))
+        }
+    });
+    EnvFileParser::write_file(&path, &vars, true)?;
+
+    println!(
+        "{} Exported {} variable(s) from {} environment to {}",
+        "✓".green(),
+        vars.len(),
+        env,
+        path.display()
+    );
+    Ok(())
+}
+
+pub fn handle_smart_command(input: String, backend: Option<String>) -> Result<()> {
     let path = Path::new(&input);
-    
+
     if path.exists() {
         // It's a file - set variables from it
-        handle_set(None, None, None, Some(path.to_path_buf()))?;
+        handle_set(None, None, None, Some(path.to_path_buf()), backend)?;
     } else if input.contains('=') {
         // It's a key=value pair
         let parts: Vec<&str> = input.splitn(2, '=').collect();
@@ -195,24 +1031,26 @@ pub fn handle_smart_command(input: String) -> Result<()> {
                 Some(parts[1].to_string()),
                 None,
                 None,
+                backend,
             )?;
         } else {
             eprintln!("{} Invalid key=value format", "✗".red());
             std::process::exit(1);
         }
     } else if input.ends_with(".env") || input.contains('/') {
-        // Looks like a file path - try to restore to it
+        // Looks like a file path - restore the development environment's
+        // variables to it.
         let git_resolver = GitPathResolver::new(None);
         let service_name = git_resolver.generate_service_name("development");
-        let _keychain = KeychainManager::new(service_name);
-        
-        // Would get vars and write to file
-        println!("{} Would restore variables to: {}", "→".blue(), input);
-        println!("{}", "Restore functionality not yet implemented".yellow());
+        let keychain = secret_store::create_store(service_name, backend.as_deref())?;
+
+        let vars = keychain.list_vars()?;
+        EnvFileParser::write_file(Path::new(&input), &vars, true)?;
+        println!("{} Restored {} variables to {}", "✓".green(), vars.len(), input);
     } else {
         // Treat as variable name to get
-        handle_get(Some(input), None, false)?;
+        handle_get(Some(input), None, false, backend)?;
     }
-    
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,156 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::keychain::KeychainManager;
+
+/// Reads the git credential helper wire format from stdin: `key=value` lines
+/// (`protocol=`, `host=`, `path=`, `username=`, `password=`) terminated by a
+/// blank line or EOF. See `git help credential`.
+fn read_git_credential_input() -> Result<HashMap<String, String>> {
+    let stdin = io::stdin();
+    let mut fields = HashMap::new();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            fields.insert(key.to_string(), value.to_string());
+        }
+    }
+    Ok(fields)
+}
+
+fn git_credential_service(fields: &HashMap<String, String>) -> String {
+    let host = fields.get("host").map(String::as_str).unwrap_or("unknown");
+    let path = fields
+        .get("path")
+        .map(|p| p.trim_end_matches(".git"))
+        .unwrap_or("");
+
+    if path.is_empty() {
+        host.to_string()
+    } else {
+        format!("{}/{}", host, path)
+    }
+}
+
+/// Acts as a `git-credential-lpop` helper: stores/retrieves/erases
+/// credentials in the OS keychain keyed by `host/path`, so `git` can
+/// delegate credential storage to `lpop` the same way it would to
+/// `osxkeychain` or `manager-core`.
+pub fn handle_git_credential(operation: &str) -> Result<()> {
+    let fields = read_git_credential_input()?;
+    let service = git_credential_service(&fields);
+    let account = fields
+        .get("username")
+        .cloned()
+        .unwrap_or_else(|| "git".to_string());
+    let keychain = KeychainManager::new(service);
+
+    match operation {
+        "get" => {
+            if let Some(password) = keychain.get_var(&account)? {
+                println!("username={}", account);
+                println!("password={}", password);
+            }
+        }
+        "store" => {
+            if let Some(password) = fields.get("password") {
+                keychain.set_var(&account, password)?;
+            }
+        }
+        "erase" => {
+            keychain.delete_var(&account)?;
+        }
+        other => bail!("Unsupported git credential operation: {}", other),
+    }
+
+    Ok(())
+}
+
+/// Also used by `external_store::CredentialProcessStore` to talk to an
+/// external helper program in the same shape, so lpop and the helper speak
+/// one wire format regardless of which end of the pipe lpop is on.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CredentialProcessRequest {
+    pub(crate) action: String,
+    pub(crate) service: String,
+    pub(crate) account: Option<String>,
+    pub(crate) value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CredentialProcessResponse {
+    pub(crate) success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cache_control: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+impl CredentialProcessResponse {
+    fn ok() -> Self {
+        Self {
+            success: true,
+            password: None,
+            cache_control: None,
+            error: None,
+        }
+    }
+
+    fn err(message: impl ToString) -> Self {
+        Self {
+            success: false,
+            password: None,
+            cache_control: None,
+            error: Some(message.to_string()),
+        }
+    }
+}
+
+/// Acts as a generic JSON credential-process helper: a single-line
+/// `{"action":"get"|"store"|"erase","service":...,"account":...}` request on
+/// stdin yields a single-line JSON response on stdout, so other tooling
+/// (registry logins, CI credential helpers) can delegate storage to the OS
+/// keychain without speaking the git-specific protocol.
+pub fn handle_credential_process() -> Result<()> {
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let request: CredentialProcessRequest = serde_json::from_str(input.trim())?;
+
+    let keychain = KeychainManager::new(request.service);
+    let account = request.account.unwrap_or_else(|| "default".to_string());
+
+    let response = match request.action.as_str() {
+        "get" => match keychain.get_var(&account) {
+            Ok(password) => CredentialProcessResponse {
+                success: password.is_some(),
+                password,
+                cache_control: Some("session".to_string()),
+                error: None,
+            },
+            Err(e) => CredentialProcessResponse::err(e),
+        },
+        "store" => match request.value {
+            Some(value) => match keychain.set_var(&account, &value) {
+                Ok(()) => CredentialProcessResponse::ok(),
+                Err(e) => CredentialProcessResponse::err(e),
+            },
+            None => CredentialProcessResponse::err("store action requires a value"),
+        },
+        "erase" => match keychain.delete_var(&account) {
+            Ok(_) => CredentialProcessResponse::ok(),
+            Err(e) => CredentialProcessResponse::err(e),
+        },
+        other => bail!("Unsupported credential-process action: {}", other),
+    };
+
+    println!("{}", serde_json::to_string(&response)?);
+    io::stdout().flush()?;
+    Ok(())
+}
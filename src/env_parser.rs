@@ -1,10 +1,56 @@
 use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
 
 pub struct EnvFileParser;
 
+/// Added/changed/removed keys between two successive parses of a watched
+/// `.env` file, as produced by `EnvFileParser::watch`.
+#[derive(Debug, Default, PartialEq)]
+pub struct EnvDiff {
+    pub added: HashMap<String, String>,
+    /// key -> (old value, new value)
+    pub changed: HashMap<String, (String, String)>,
+    pub removed: Vec<String>,
+}
+
+impl EnvDiff {
+    fn between(before: &HashMap<String, String>, after: &HashMap<String, String>) -> Self {
+        let mut diff = EnvDiff::default();
+        for (key, value) in after {
+            match before.get(key) {
+                None => {
+                    diff.added.insert(key.clone(), value.clone());
+                }
+                Some(old) if old != value => {
+                    diff.changed.insert(key.clone(), (old.clone(), value.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        for key in before.keys() {
+            if !after.contains_key(key) {
+                diff.removed.push(key.clone());
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Handle for a background `EnvFileParser::watch`; holds the OS-level
+/// watcher alive and stops it on drop.
+pub struct EnvWatcher {
+    _watcher: RecommendedWatcher,
+}
+
 impl EnvFileParser {
     pub fn parse_file(path: &Path) -> Result<HashMap<String, String>> {
         let content = fs::read_to_string(path)
@@ -182,6 +228,73 @@ impl EnvFileParser {
             value.to_string()
         }
     }
+
+    /// Watches `path` for changes, re-parsing and diffing against the
+    /// previously parsed state on every modification. Diffs are delivered
+    /// through the returned channel rather than a callback, so the caller
+    /// decides whether to process them on its own thread or fan them out.
+    ///
+    /// Rapid successive writes (e.g. an editor's autosave) are coalesced by
+    /// waiting `debounce` after the last event before re-parsing. The parent
+    /// directory is watched rather than the file itself, since many editors
+    /// save by writing a temp file and renaming it over the original
+    /// (atomic replace); watching the file directly loses track of it the
+    /// moment its inode is replaced.
+    pub fn watch(path: PathBuf, debounce: Duration) -> Result<(EnvWatcher, mpsc::Receiver<EnvDiff>)> {
+        let parent = path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+
+        let (event_tx, event_rx) = mpsc::channel::<()>();
+        // Match events by file name: the watcher may report paths joined
+        // onto `parent` (e.g. `./.env`) or made absolute, so they need not
+        // equal `path` as given.
+        let watch_name = path.file_name().map(|name| name.to_os_string());
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let relevant = matches!(
+                    event.kind,
+                    EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                ) && watch_name.is_some()
+                    && event.paths.iter().any(|p| p.file_name() == watch_name.as_deref());
+                if relevant {
+                    let _ = event_tx.send(());
+                }
+            }
+        })
+        .context("Failed to create file watcher")?;
+
+        watcher
+            .watch(&parent, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch directory: {}", parent.display()))?;
+
+        let (diff_tx, diff_rx) = mpsc::channel::<EnvDiff>();
+        std::thread::spawn(move || {
+            let mut previous = Self::parse_file(&path).unwrap_or_default();
+
+            while event_rx.recv().is_ok() {
+                // Coalesce any further events that arrive within `debounce`,
+                // so one editor save doesn't fire the diff multiple times.
+                while event_rx.recv_timeout(debounce).is_ok() {}
+
+                // The file may briefly not exist mid atomic-replace; skip
+                // this round and let the next event (the replacement's
+                // create/modify) trigger a re-parse.
+                if let Ok(current) = Self::parse_file(&path) {
+                    let diff = EnvDiff::between(&previous, &current);
+                    previous = current;
+                    if !diff.is_empty() && diff_tx.send(diff).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok((EnvWatcher { _watcher: watcher }, diff_rx))
+    }
 }
 
 #[cfg(test)]
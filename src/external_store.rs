@@ -0,0 +1,317 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::credential_helper::{CredentialProcessRequest, CredentialProcessResponse};
+use crate::secret_store::SecretStore;
+
+/// `SecretStore` that delegates every call to an external helper program
+/// instead of the OS keychain: one `CredentialProcessRequest` JSON line on
+/// the child's stdin, one `CredentialProcessResponse` JSON line back on its
+/// stdout. It's the same wire format `credential_helper::handle_credential_process`
+/// answers, so a helper written against one end works against the other —
+/// this lets a team point lpop at an in-house credential daemon without
+/// lpop knowing anything about it beyond the command to run.
+pub struct CredentialProcessStore {
+    service: String,
+    command: String,
+    args: Vec<String>,
+}
+
+impl CredentialProcessStore {
+    pub fn new(service: String, command: String, args: Vec<String>) -> Self {
+        Self { service, command, args }
+    }
+
+    fn call(&self, action: &str, account: &str, value: Option<String>) -> Result<CredentialProcessResponse> {
+        let request = CredentialProcessRequest {
+            action: action.to_string(),
+            service: self.service.clone(),
+            account: Some(account.to_string()),
+            value,
+        };
+
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to spawn credential helper '{}'", self.command))?;
+
+        {
+            let stdin = child
+                .stdin
+                .as_mut()
+                .context("Credential helper did not expose stdin")?;
+            stdin.write_all(serde_json::to_string(&request)?.as_bytes())?;
+            stdin.write_all(b"\n")?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("Credential helper '{}' failed to run", self.command))?;
+        if !output.status.success() {
+            bail!("Credential helper '{}' exited with {}", self.command, output.status);
+        }
+
+        let line = String::from_utf8(output.stdout)
+            .context("Credential helper produced non-UTF8 output")?;
+        serde_json::from_str(line.trim())
+            .with_context(|| format!("Failed to parse response from '{}'", self.command))
+    }
+}
+
+impl SecretStore for CredentialProcessStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        let response = self.call("get", key, None)?;
+        if response.success {
+            Ok(response.password)
+        } else {
+            bail!(response.error.unwrap_or_else(|| "credential helper get failed".to_string()))
+        }
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        let response = self.call("store", key, Some(value.to_string()))?;
+        if response.success {
+            Ok(())
+        } else {
+            bail!(response.error.unwrap_or_else(|| "credential helper store failed".to_string()))
+        }
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        let response = self.call("erase", key, None)?;
+        if response.success {
+            Ok(true)
+        } else {
+            bail!(response.error.unwrap_or_else(|| "credential helper erase failed".to_string()))
+        }
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        bail!("The credential-process protocol has no bulk-erase operation; delete variables one at a time")
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        bail!("The credential-process protocol has no enumeration operation")
+    }
+}
+
+/// Runs 1Password's `op` CLI, storing each variable as an item named
+/// `<service>-<key>` with a single `password` field in the given vault.
+/// Requires the caller already be signed in (`op signin`) or have
+/// `OP_SERVICE_ACCOUNT_TOKEN` set; lpop doesn't manage that session.
+pub struct OnePasswordStore {
+    service: String,
+    vault: String,
+}
+
+impl OnePasswordStore {
+    pub fn new(service: String, vault: String) -> Self {
+        Self { service, vault }
+    }
+
+    fn item_title(&self, key: &str) -> String {
+        format!("{}-{}", self.service, key)
+    }
+
+    fn run(&self, args: &[&str]) -> Result<std::process::Output> {
+        Command::new("op")
+            .args(args)
+            .output()
+            .context("Failed to run 'op'; is the 1Password CLI installed?")
+    }
+}
+
+impl SecretStore for OnePasswordStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        let output = self.run(&[
+            "item",
+            "get",
+            &self.item_title(key),
+            "--vault",
+            &self.vault,
+            "--fields",
+            "password",
+            "--reveal",
+        ])?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let value = String::from_utf8(output.stdout)?.trim().to_string();
+        Ok(if value.is_empty() { None } else { Some(value) })
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        let title = self.item_title(key);
+        let password_field = format!("password={}", value);
+        // `op` has no upsert; delete-then-create mirrors how `KeychainManager`
+        // overwrites existing entries via the OS keyring.
+        let _ = self.run(&["item", "delete", &title, "--vault", &self.vault]);
+        let output = self.run(&[
+            "item",
+            "create",
+            "--category",
+            "password",
+            "--title",
+            &title,
+            "--vault",
+            &self.vault,
+            &password_field,
+        ])?;
+        if !output.status.success() {
+            bail!("'op item create' failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(())
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        let output = self.run(&["item", "delete", &self.item_title(key), "--vault", &self.vault])?;
+        Ok(output.status.success())
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        bail!("OnePasswordStore does not support clear_all; delete variables one at a time")
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        bail!("OnePasswordStore does not support list_vars; 'op' has no per-variable reverse lookup by service prefix")
+    }
+}
+
+/// Runs `pass` (the standard Unix password manager), storing each variable
+/// under the path `<service>/<key>` in the user's password store.
+pub struct PassStore {
+    service: String,
+}
+
+impl PassStore {
+    pub fn new(service: String) -> Self {
+        Self { service }
+    }
+
+    fn entry_path(&self, key: &str) -> String {
+        format!("{}/{}", self.service, key)
+    }
+}
+
+impl SecretStore for PassStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        let output = Command::new("pass")
+            .args(["show", &self.entry_path(key)])
+            .output()
+            .context("Failed to run 'pass'; is the password-store CLI installed?")?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let value = String::from_utf8(output.stdout)?
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string();
+        Ok(if value.is_empty() { None } else { Some(value) })
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        let mut child = Command::new("pass")
+            .args(["insert", "-f", &self.entry_path(key)])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .context("Failed to run 'pass'; is the password-store CLI installed?")?;
+        {
+            let stdin = child.stdin.as_mut().context("'pass insert' did not expose stdin")?;
+            writeln!(stdin, "{}", value)?;
+            writeln!(stdin, "{}", value)?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            bail!("'pass insert' exited with {}", status);
+        }
+        Ok(())
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        let output = Command::new("pass")
+            .args(["rm", "-f", &self.entry_path(key)])
+            .output()
+            .context("Failed to run 'pass'; is the password-store CLI installed?")?;
+        Ok(output.status.success())
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        bail!("PassStore does not support clear_all; delete variables one at a time")
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        bail!("PassStore does not support list_vars; 'pass' has no index of entries under a given prefix without `pass ls`'s tree output")
+    }
+}
+
+/// Shells out to macOS's built-in `security` CLI directly, bypassing the
+/// `keyring` crate. Teams that already drive `security` for other tooling
+/// can back lpop with it the same way, without lpop's own `keyring`-backed
+/// `KeychainManager` entering the picture.
+#[cfg(target_os = "macos")]
+pub struct SecurityCliStore {
+    service: String,
+}
+
+#[cfg(target_os = "macos")]
+impl SecurityCliStore {
+    pub fn new(service: String) -> Self {
+        Self { service }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl SecretStore for SecurityCliStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        let output = Command::new("security")
+            .args(["find-generic-password", "-s", &self.service, "-a", key, "-w"])
+            .output()
+            .context("Failed to run 'security'")?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let value = String::from_utf8(output.stdout)?.trim().to_string();
+        Ok(if value.is_empty() { None } else { Some(value) })
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        // `-U` updates an existing item in place instead of failing with
+        // "already exists", mirroring KeychainManager::set_var's overwrite semantics.
+        let output = Command::new("security")
+            .args([
+                "add-generic-password",
+                "-s", &self.service,
+                "-a", key,
+                "-w", value,
+                "-U",
+            ])
+            .output()
+            .context("Failed to run 'security'")?;
+        if !output.status.success() {
+            bail!("'security add-generic-password' failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(())
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        let output = Command::new("security")
+            .args(["delete-generic-password", "-s", &self.service, "-a", key])
+            .output()
+            .context("Failed to run 'security'")?;
+        Ok(output.status.success())
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        bail!("SecurityCliStore does not support clear_all; delete variables one at a time")
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        bail!("SecurityCliStore does not support list_vars; 'security' has no query-by-service enumeration")
+    }
+}
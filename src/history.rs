@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::keychain::KeychainManager;
+
+const LOG_KEY: &str = "__lpop_log__";
+const CHECKPOINT_KEY: &str = "__lpop_checkpoint__";
+
+/// Matches Aerogramme's Bayou KEEP_STATE_EVERY: take a fresh checkpoint (and
+/// prune the ops that predate it) every this-many operations.
+const KEEP_STATE_EVERY: usize = 64;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum OpKind {
+    Set,
+    Delete,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub timestamp: String,
+    pub kind: OpKind,
+    pub key: String,
+    pub value: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Checkpoint {
+    state: HashMap<String, String>,
+}
+
+static TIEBREAKER: AtomicU64 = AtomicU64::new(0);
+
+/// Produces a monotonic, lexicographically sortable timestamp: nanoseconds
+/// since the epoch, zero-padded, with a per-process counter suffix so two
+/// operations issued within the same nanosecond still sort distinctly.
+pub fn next_timestamp() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let tiebreaker = TIEBREAKER.fetch_add(1, Ordering::SeqCst);
+    format!("{:020}-{:010}", nanos, tiebreaker)
+}
+
+fn apply_op(state: &mut HashMap<String, String>, op: &Operation) {
+    match op.kind {
+        OpKind::Set => {
+            state.insert(op.key.clone(), op.value.clone().unwrap_or_default());
+        }
+        OpKind::Delete => {
+            state.remove(&op.key);
+        }
+    }
+}
+
+/// Append-only log of `Set`/`Delete` operations for one environment, plus a
+/// periodic checkpoint of the fully materialized state, modeled on
+/// Aerogramme's Bayou. The log tail and the checkpoint are themselves stored
+/// through `KeychainManager` under reserved accounts, the same way the
+/// `__lpop_index__` entry tracks known keys.
+pub struct HistoryLog {
+    keychain: KeychainManager,
+}
+
+impl HistoryLog {
+    pub fn new(service_name: String) -> Self {
+        Self {
+            keychain: KeychainManager::new(service_name),
+        }
+    }
+
+    fn read_checkpoint(&self) -> Result<Checkpoint> {
+        match self.keychain.get_var(CHECKPOINT_KEY)? {
+            Some(serialized) => {
+                serde_json::from_str(&serialized).context("Failed to parse history checkpoint")
+            }
+            None => Ok(Checkpoint::default()),
+        }
+    }
+
+    fn write_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.keychain
+            .set_var(CHECKPOINT_KEY, &serde_json::to_string(checkpoint)?)
+    }
+
+    fn read_tail(&self) -> Result<Vec<Operation>> {
+        match self.keychain.get_var(LOG_KEY)? {
+            Some(serialized) => serde_json::from_str(&serialized).context("Failed to parse history log"),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn write_tail(&self, ops: &[Operation]) -> Result<()> {
+        self.keychain.set_var(LOG_KEY, &serde_json::to_string(ops)?)
+    }
+
+    /// Appends a `{timestamp, kind, key, value}` op to the tail, taking a
+    /// fresh checkpoint (and pruning the tail) once it reaches
+    /// `KEEP_STATE_EVERY` entries.
+    pub fn record(&self, kind: OpKind, key: &str, value: Option<String>) -> Result<()> {
+        let mut tail = self.read_tail()?;
+        tail.push(Operation {
+            timestamp: next_timestamp(),
+            kind,
+            key: key.to_string(),
+            value,
+        });
+
+        if tail.len() >= KEEP_STATE_EVERY {
+            let mut state = self.read_checkpoint()?.state;
+            for op in &tail {
+                apply_op(&mut state, op);
+            }
+            self.write_checkpoint(&Checkpoint { state })?;
+            self.write_tail(&[])?;
+        } else {
+            self.write_tail(&tail)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ops recorded since the last checkpoint, oldest first, for `lpop history`.
+    pub fn list_ops(&self) -> Result<Vec<Operation>> {
+        self.read_tail()
+    }
+
+    /// Materializes state as of `at_or_before` (inclusive) by loading the
+    /// latest checkpoint and replaying tail ops up to that timestamp.
+    pub fn state_as_of(&self, at_or_before: &str) -> Result<HashMap<String, String>> {
+        let mut state = self.read_checkpoint()?.state;
+        for op in self.read_tail()? {
+            if op.timestamp.as_str() > at_or_before {
+                break;
+            }
+            apply_op(&mut state, &op);
+        }
+        Ok(state)
+    }
+}
@@ -1,7 +1,36 @@
 use anyhow::{Context, Result};
 use keyring::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use crate::git_resolver::GitPathResolver;
+
+/// Reserved account name holding the serialized set of keys this service has
+/// stored. The `keyring` crate has no way to enumerate a service's entries,
+/// so this index is what makes `list_vars`/`clear_all` actually work: it's
+/// rewritten after every `set_var`/`delete_var`, as a separate keychain write
+/// from the entry itself, so the two are not updated atomically.
+const INDEX_KEY: &str = "__lpop_index__";
+
+/// Filters for `KeychainManager::find_entries`, which enumerates entries
+/// across every environment for a repo (not just the single service a
+/// `KeychainManager` instance is scoped to).
+#[derive(Default)]
+pub struct FindQuery {
+    /// Restrict to entries whose service name encodes this environment.
+    pub environment: Option<String>,
+    /// Restrict to entries whose account (key) name starts with this prefix.
+    pub account_prefix: Option<String>,
+}
+
+/// Talks directly to the OS keyring (via `keyring::Entry`); this is the
+/// default backend everywhere except macOS debug builds (where
+/// `secret_store::create_store` defaults to `security`), and its behavior is
+/// unchanged so existing callers (including the debug
+/// examples in `examples/`) keep working as-is. Runtime-selectable backends
+/// — this, `InMemorySecretStore`, `EncryptedFileSecretStore` — live behind
+/// the `secret_store::SecretStore` trait; go through `secret_store::create_store`
+/// rather than constructing `KeychainManager` directly so CI/headless hosts
+/// with no usable keychain can pick another backend.
 pub struct KeychainManager {
     service_name: String,
 }
@@ -10,57 +39,230 @@ impl KeychainManager {
     pub fn new(service_name: String) -> Self {
         Self { service_name }
     }
-    
+
     pub fn set_var(&self, key: &str, value: &str) -> Result<()> {
         let entry = Entry::new_with_target("Protected",&self.service_name, key)?;
-        println!("Setting {} in keychain for service {}", key, self.service_name);
         entry.set_password(value).with_context(|| format!("Failed to set {} in keychain", key))?;
-        // The `get_var` method returns a `Result<Option<String>>`, which cannot be directly formatted with `{}`.
-        // For debugging, you might want to print the result of `get_var` using `{:?}` or handle the `Result` and `Option` explicitly.
-        let retrieved_value = self.get_var(key)?;
-        println!("Value in keychain is: {:?}", retrieved_value);
+
+        let mut index = self.read_index()?;
+        index.insert(key.to_string());
+        self.write_index(&index)?;
+
         Ok(())
     }
-    
+
     pub fn get_var(&self, key: &str) -> Result<Option<String>> {
         let entry = Entry::new_with_target("Protected",&self.service_name, key)?;
-        println!("Getting {} in keychain for service {}", key, self.service_name);
         match entry.get_password() {
             Ok(password) => Ok(Some(password)),
-            // Err(keyring::Error::NoEntry) => Ok(None),
+            Err(keyring::Error::NoEntry) => Ok(None),
             Err(e) => Err(e).context("Failed to read from keychain"),
         }
     }
-    
+
     pub fn delete_var(&self, key: &str) -> Result<bool> {
-        let entry = Entry::new(&self.service_name, key)?;
-        match entry.delete_credential() {
-            Ok(()) => Ok(true),
-            Err(keyring::Error::NoEntry) => Ok(false),
-            Err(e) => Err(e).context("Failed to delete from keychain"),
+        let entry = Entry::new_with_target("Protected", &self.service_name, key)?;
+        let deleted = match entry.delete_credential() {
+            Ok(()) => true,
+            Err(keyring::Error::NoEntry) => false,
+            Err(e) => return Err(e).context("Failed to delete from keychain"),
+        };
+
+        let mut index = self.read_index()?;
+        if index.remove(key) {
+            self.write_index(&index)?;
         }
+
+        Ok(deleted)
     }
-    
+
     pub fn list_vars(&self) -> Result<HashMap<String, String>> {
-        // Note: keyring crate doesn't support listing all entries
-        // This is a limitation we'll need to work around
-        // For now, return empty - in real implementation we'd need to
-        // track keys separately or use platform-specific APIs
-        Ok(HashMap::new())
+        let index = self.reconciled_index()?;
+
+        let mut vars = HashMap::new();
+        for key in &index {
+            // Reserved `__lpop_…__` accounts (e.g. `HistoryLog`'s log and
+            // checkpoint) are bookkeeping, not variables.
+            if key.starts_with("__lpop_") && key.ends_with("__") {
+                continue;
+            }
+            if let Some(value) = self.get_var(key)? {
+                vars.insert(key.clone(), value);
+            }
+        }
+        Ok(vars)
     }
-    
+
     pub fn set_vars(&self, vars: HashMap<String, String>) -> Result<()> {
         for (key, value) in vars {
             self.set_var(&key, &value)?;
         }
         Ok(())
     }
-    
+
     pub fn clear_all(&self) -> Result<()> {
-        // Would need to track keys or use platform-specific APIs
-        // For now, this is a no-op
-        Ok(())
+        let index = self.reconciled_index()?;
+
+        for key in &index {
+            let entry = Entry::new_with_target("Protected", &self.service_name, key)?;
+            match entry.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                Err(e) => return Err(e).context(format!("Failed to delete {} from keychain", key)),
+            }
+        }
+
+        let index_entry = Entry::new(&self.service_name, INDEX_KEY)?;
+        match index_entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).context("Failed to delete keychain index"),
+        }
+    }
+
+    fn read_index(&self) -> Result<BTreeSet<String>> {
+        let entry = Entry::new(&self.service_name, INDEX_KEY)?;
+        match entry.get_password() {
+            Ok(serialized) => {
+                let keys: BTreeSet<String> =
+                    serde_json::from_str(&serialized).context("Failed to parse keychain index")?;
+                Ok(keys)
+            }
+            Err(keyring::Error::NoEntry) => Ok(BTreeSet::new()),
+            Err(e) => Err(e).context("Failed to read keychain index"),
+        }
+    }
+
+    fn write_index(&self, keys: &BTreeSet<String>) -> Result<()> {
+        let entry = Entry::new(&self.service_name, INDEX_KEY)?;
+        let serialized = serde_json::to_string(keys).context("Failed to serialize keychain index")?;
+        entry
+            .set_password(&serialized)
+            .context("Failed to write keychain index")
+    }
+
+    /// Reads the tracked index and, on macOS, merges in anything `security
+    /// dump-keychain` can see for this service but the index is missing —
+    /// recovering from drift (e.g. an index write that was interrupted, or
+    /// an entry added outside of `lpop`).
+    fn reconciled_index(&self) -> Result<BTreeSet<String>> {
+        let mut index = self.read_index()?;
+
+        #[cfg(target_os = "macos")]
+        {
+            let discovered = self.discover_macos_keys();
+            if !discovered.is_empty() {
+                let before = index.len();
+                index.extend(discovered);
+                if index.len() != before {
+                    self.write_index(&index)?;
+                }
+            }
+        }
+
+        Ok(index)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn discover_macos_keys(&self) -> BTreeSet<String> {
+        let output = match std::process::Command::new("/usr/bin/security")
+            .args(["dump-keychain", "-d"])
+            .output()
+        {
+            Ok(output) if output.status.success() => output,
+            _ => return BTreeSet::new(),
+        };
+
+        let dump = String::from_utf8_lossy(&output.stdout);
+        let mut service = String::new();
+        let mut account = String::new();
+        let mut keys = BTreeSet::new();
+
+        for line in dump.lines() {
+            let line = line.trim();
+            if let Some(value) = line.strip_prefix("\"svce\"<blob>=") {
+                service = value.trim_matches('"').to_string();
+            } else if let Some(value) = line.strip_prefix("\"acct\"<blob>=") {
+                account = value.trim_matches('"').to_string();
+            } else if line.starts_with("password: ") {
+                if service == self.service_name && !account.is_empty() && account != INDEX_KEY {
+                    keys.insert(account.clone());
+                }
+                service.clear();
+                account.clear();
+            }
+        }
+
+        keys
     }
+
+    /// Enumerate stored entries for `repo` across every environment,
+    /// filtered by `query`, returning `(environment, key, value)` triples.
+    /// Like `reconciled_index`'s drift recovery, this only works on macOS:
+    /// the `keyring` crate has no cross-service enumeration, so it shells
+    /// out to `security dump-keychain` to discover candidates, then reads
+    /// each value back through `keyring` rather than trusting the dump.
+    #[cfg(target_os = "macos")]
+    pub fn find_entries(repo: &str, query: &FindQuery) -> Result<Vec<(String, String, String)>> {
+        let output = std::process::Command::new("/usr/bin/security")
+            .args(["dump-keychain", "-d"])
+            .output()
+            .context("Failed to run security dump-keychain")?;
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        let dump = String::from_utf8_lossy(&output.stdout);
+        let mut service = String::new();
+        let mut account = String::new();
+        let mut results = Vec::new();
+
+        for line in dump.lines() {
+            let line = line.trim();
+            if let Some(value) = line.strip_prefix("\"svce\"<blob>=") {
+                service = value.trim_matches('"').to_string();
+            } else if let Some(value) = line.strip_prefix("\"acct\"<blob>=") {
+                account = value.trim_matches('"').to_string();
+            } else if line.starts_with("password: ") {
+                if matches_query(&service, &account, repo, query) {
+                    if let Ok(Some(password)) = KeychainManager::new(service.clone()).get_var(&account) {
+                        let environment = GitPathResolver::extract_env_from_service(&service).to_string();
+                        results.push((environment, account.clone(), password));
+                    }
+                }
+                service.clear();
+                account.clear();
+            }
+        }
+
+        Ok(results)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn find_entries(_repo: &str, _query: &FindQuery) -> Result<Vec<(String, String, String)>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Whether a dumped `(service, account)` pair belongs to `repo` and matches
+/// `query`. Split out from `find_entries` so the filtering logic is testable
+/// without shelling out to `security`.
+fn matches_query(service: &str, account: &str, repo: &str, query: &FindQuery) -> bool {
+    if service.is_empty() || account.is_empty() || account == INDEX_KEY {
+        return false;
+    }
+    if GitPathResolver::extract_repo_from_service(service) != repo {
+        return false;
+    }
+    if let Some(environment) = &query.environment {
+        if GitPathResolver::extract_env_from_service(service) != environment {
+            return false;
+        }
+    }
+    if let Some(prefix) = &query.account_prefix {
+        if !account.starts_with(prefix.as_str()) {
+            return false;
+        }
+    }
+    true
 }
 
 #[cfg(test)]
@@ -123,6 +325,60 @@ mod tests {
         assert_eq!(value, None);
     }
 
+    #[test]
+    fn test_matches_query_filters_by_repo_and_environment() {
+        let query = FindQuery {
+            environment: Some("production".to_string()),
+            account_prefix: None,
+        };
+
+        assert!(matches_query(
+            "github.com/acme/widgets?env=production",
+            "API_KEY",
+            "github.com/acme/widgets",
+            &query
+        ));
+        assert!(!matches_query(
+            "github.com/acme/widgets?env=staging",
+            "API_KEY",
+            "github.com/acme/widgets",
+            &query
+        ));
+        assert!(!matches_query(
+            "github.com/other/repo?env=production",
+            "API_KEY",
+            "github.com/acme/widgets",
+            &query
+        ));
+    }
+
+    #[test]
+    fn test_matches_query_filters_by_account_prefix_and_skips_index() {
+        let query = FindQuery {
+            environment: None,
+            account_prefix: Some("DB_".to_string()),
+        };
+
+        assert!(matches_query(
+            "local/app?env=development",
+            "DB_HOST",
+            "local/app",
+            &query
+        ));
+        assert!(!matches_query(
+            "local/app?env=development",
+            "API_KEY",
+            "local/app",
+            &query
+        ));
+        assert!(!matches_query(
+            "local/app?env=development",
+            INDEX_KEY,
+            "local/app",
+            &query
+        ));
+    }
+
     #[test]
     #[serial]
     fn test_set_multiple_vars() {
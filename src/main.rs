@@ -1,7 +1,14 @@
+mod agent;
 mod cli;
+mod credential_helper;
 mod env_parser;
+mod external_store;
 mod git_resolver;
+mod history;
 mod keychain;
+mod secret_store;
+mod sync;
+mod vault;
 
 use anyhow::Result;
 use clap::Parser;
@@ -9,29 +16,69 @@ use cli::{Cli, Commands};
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    
+    let backend = cli.backend.clone();
+
     match cli.command {
         Some(Commands::Get { key, env, all }) => {
-            cli::handle_get(key, env, all)?;
+            cli::handle_get(key, env, all, backend)?;
         }
         Some(Commands::Set { key, value, env, file }) => {
-            cli::handle_set(key, value, env, file)?;
+            cli::handle_set(key, value, env, file, backend)?;
         }
         Some(Commands::Delete { key, env, all }) => {
-            cli::handle_delete(key, env, all)?;
+            cli::handle_delete(key, env, all, backend)?;
         }
         Some(Commands::List { env }) => {
-            cli::handle_list(env)?;
+            cli::handle_list(env, backend)?;
+        }
+        Some(Commands::Vault { action }) => {
+            cli::handle_vault(action, backend)?;
+        }
+        Some(Commands::GitCredential { operation }) => {
+            credential_helper::handle_git_credential(&operation)?;
+        }
+        Some(Commands::CredentialProcess) => {
+            credential_helper::handle_credential_process()?;
+        }
+        Some(Commands::History { env }) => {
+            cli::handle_history(env)?;
+        }
+        Some(Commands::Rollback { timestamp, env }) => {
+            cli::handle_rollback(timestamp, env, backend)?;
+        }
+        Some(Commands::Export { env, out }) => {
+            cli::handle_export(env, out, backend)?;
+        }
+        Some(Commands::Import { file, env }) => {
+            cli::handle_import(file, env, backend)?;
+        }
+        Some(Commands::Sync { action }) => {
+            cli::handle_sync(action, backend)?;
+        }
+        Some(Commands::Apply { file, env, dry_run, prune }) => {
+            cli::handle_apply(file, env, dry_run, prune, backend)?;
+        }
+        Some(Commands::Watch { file, env, prune }) => {
+            cli::handle_watch(file, env, prune, backend)?;
+        }
+        Some(Commands::Agent { action }) => {
+            cli::handle_agent(action)?;
+        }
+        Some(Commands::ImportEnv { file, env, skip, overwrite, delete_after }) => {
+            cli::handle_import_env(file, env, skip, overwrite, delete_after, backend)?;
+        }
+        Some(Commands::ExportEnv { env, out, stdout }) => {
+            cli::handle_export_env(env, out, stdout, backend)?;
         }
         None => {
             // Smart command inference
             if let Some(input) = cli.input {
-                cli::handle_smart_command(input)?;
+                cli::handle_smart_command(input, backend)?;
             } else {
-                cli::handle_get(None, Some(cli.env), false)?;
+                cli::handle_get(None, Some(cli.env), false, backend)?;
             }
         }
     }
-    
+
     Ok(())
-}
\ No newline at end of file
+}
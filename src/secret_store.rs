@@ -0,0 +1,290 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::keychain::KeychainManager;
+use crate::vault;
+
+/// Storage-backend abstraction for where `lpop` actually persists variables.
+/// `KeychainManager` (the OS keychain) is the default, but handlers go
+/// through this trait instead of constructing it directly so the same
+/// commands work on hosts with no system keychain (headless Linux, CI),
+/// backed by `InMemorySecretStore` or `EncryptedFileSecretStore` instead.
+pub trait SecretStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>>;
+    fn set_var(&self, key: &str, value: &str) -> Result<()>;
+    fn delete_var(&self, key: &str) -> Result<bool>;
+    fn clear_all(&self) -> Result<()>;
+    fn list_vars(&self) -> Result<HashMap<String, String>>;
+
+    fn set_vars(&self, vars: HashMap<String, String>) -> Result<()> {
+        for (key, value) in vars {
+            self.set_var(&key, &value)?;
+        }
+        Ok(())
+    }
+}
+
+impl SecretStore for KeychainManager {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        KeychainManager::get_var(self, key)
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        KeychainManager::set_var(self, key, value)
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        KeychainManager::delete_var(self, key)
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        KeychainManager::clear_all(self)
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        KeychainManager::list_vars(self)
+    }
+
+    fn set_vars(&self, vars: HashMap<String, String>) -> Result<()> {
+        KeychainManager::set_vars(self, vars)
+    }
+}
+
+/// Pure in-memory backend with no persistence across process invocations.
+/// Exists for tests and CI environments with no keychain to talk to.
+#[derive(Default)]
+pub struct InMemorySecretStore {
+    vars: Mutex<HashMap<String, String>>,
+}
+
+impl InMemorySecretStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SecretStore for InMemorySecretStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.vars.lock().unwrap().get(key).cloned())
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        self.vars.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        Ok(self.vars.lock().unwrap().remove(key).is_some())
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        self.vars.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        Ok(self.vars.lock().unwrap().clone())
+    }
+}
+
+/// Backend that keeps one `lpop vault`-format encrypted file per service on
+/// disk under `~/.config/lpop/stores/`, re-encrypting the whole file on every
+/// write. Reuses the same Argon2id + XChaCha20-Poly1305 format `lpop vault
+/// export`/`import` already speak.
+pub struct EncryptedFileSecretStore {
+    path: PathBuf,
+    passphrase: String,
+}
+
+impl EncryptedFileSecretStore {
+    pub fn new(service_name: &str, passphrase: String) -> Result<Self> {
+        let dir = store_dir()?;
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create store directory: {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", sanitize_service_name(service_name)));
+        Ok(Self { path, passphrase })
+    }
+
+    fn read_all(&self) -> Result<HashMap<String, String>> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        vault::import_vars(&self.passphrase, &self.path)
+    }
+
+    fn write_all(&self, vars: &HashMap<String, String>) -> Result<()> {
+        vault::export_vars(vars, &self.passphrase, &self.path)
+    }
+}
+
+impl SecretStore for EncryptedFileSecretStore {
+    fn get_var(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.read_all()?.get(key).cloned())
+    }
+
+    fn set_var(&self, key: &str, value: &str) -> Result<()> {
+        let mut vars = self.read_all()?;
+        vars.insert(key.to_string(), value.to_string());
+        self.write_all(&vars)
+    }
+
+    fn delete_var(&self, key: &str) -> Result<bool> {
+        let mut vars = self.read_all()?;
+        let removed = vars.remove(key).is_some();
+        if removed {
+            self.write_all(&vars)?;
+        }
+        Ok(removed)
+    }
+
+    fn clear_all(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .with_context(|| format!("Failed to remove store file: {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn list_vars(&self) -> Result<HashMap<String, String>> {
+        self.read_all()
+    }
+}
+
+/// `LPOP_STORE_DIR` overrides where the `file` backend keeps its per-service
+/// vault files, mainly so tests can point it at a temp directory instead of
+/// a real `$HOME`.
+fn store_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("LPOP_STORE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = std::env::var_os("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config").join("lpop").join("stores"))
+}
+
+/// On macOS, the `keyring` backend ties each entry's ACL to the signing
+/// identity of the binary that wrote it, so every debug rebuild re-prompts
+/// for access to variables a previous build stored. Default debug builds
+/// there to the `security` backend instead, which keys entries off the
+/// service name alone and survives rebuilds; everything else keeps using
+/// `keyring`. An explicit `--backend`/`LPOP_BACKEND` always wins over this.
+fn default_backend_name() -> &'static str {
+    if cfg!(all(target_os = "macos", debug_assertions)) {
+        "security"
+    } else {
+        "keychain"
+    }
+}
+
+fn sanitize_service_name(service_name: &str) -> String {
+    service_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Resolves the effective backend name for a `--backend`/`LPOP_BACKEND`
+/// value, applying the same default `create_store` would. `KeychainManager`
+/// `find_entries`'s repo-wide, cross-environment enumeration only exists for
+/// the real OS keychain (it shells out to `security dump-keychain` on
+/// macOS), so callers that need to know whether that applies call this
+/// instead of re-deriving the default themselves.
+pub fn resolved_backend_name(backend: Option<&str>) -> &str {
+    backend.unwrap_or_else(default_backend_name)
+}
+
+/// Selects a `SecretStore` implementation by name (`"keychain"`, `"memory"`,
+/// `"file"`, `"agent"`, `"op"`, `"pass"`, `"security"`, or `"helper"`), the
+/// same string accepted by `--backend` / `LPOP_BACKEND`; with no name it uses
+/// `default_backend_name` (`"security"` in macOS debug builds, `"keychain"`
+/// otherwise).
+/// The `file` backend reads its passphrase from `LPOP_STORE_PASSPHRASE`;
+/// `"agent"` instead forwards every call to a running `lpop agent start`, so
+/// the passphrase only needs to be entered once via `lpop agent unlock`. The
+/// remaining backends delegate storage to an external program instead of the
+/// OS keychain: `"op"` and `"pass"` drive the named CLI directly; `"security"`
+/// drives macOS's `security` CLI directly; `"helper"` speaks the generic
+/// credential-process JSON protocol to a command configured via
+/// `LPOP_HELPER_COMMAND` (and optional space-separated `LPOP_HELPER_ARGS`).
+pub fn create_store(service_name: String, backend: Option<&str>) -> Result<Box<dyn SecretStore>> {
+    match resolved_backend_name(backend) {
+        "keychain" => Ok(Box::new(KeychainManager::new(service_name))),
+        "memory" => Ok(Box::new(InMemorySecretStore::new())),
+        "file" => {
+            let passphrase = std::env::var("LPOP_STORE_PASSPHRASE")
+                .context("LPOP_STORE_PASSPHRASE must be set to use the file backend")?;
+            Ok(Box::new(EncryptedFileSecretStore::new(&service_name, passphrase)?))
+        }
+        "agent" => Ok(Box::new(crate::agent::AgentSecretStore::new(service_name))),
+        "op" => {
+            let vault = std::env::var("LPOP_OP_VAULT")
+                .context("LPOP_OP_VAULT must be set to use the op backend")?;
+            Ok(Box::new(crate::external_store::OnePasswordStore::new(service_name, vault)))
+        }
+        "pass" => Ok(Box::new(crate::external_store::PassStore::new(service_name))),
+        "security" => {
+            #[cfg(target_os = "macos")]
+            {
+                Ok(Box::new(crate::external_store::SecurityCliStore::new(service_name)))
+            }
+            #[cfg(not(target_os = "macos"))]
+            {
+                anyhow::bail!("The security backend is only available on macOS")
+            }
+        }
+        "helper" => {
+            let command = std::env::var("LPOP_HELPER_COMMAND")
+                .context("LPOP_HELPER_COMMAND must be set to use the helper backend")?;
+            let args = std::env::var("LPOP_HELPER_ARGS")
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+            Ok(Box::new(crate::external_store::CredentialProcessStore::new(service_name, command, args)))
+        }
+        other => anyhow::bail!(
+            "Unknown backend '{}'; expected keychain, memory, file, agent, op, pass, security, or helper",
+            other
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_store_roundtrip() {
+        let store = InMemorySecretStore::new();
+        store.set_var("KEY", "value").unwrap();
+        assert_eq!(store.get_var("KEY").unwrap(), Some("value".to_string()));
+        assert!(store.delete_var("KEY").unwrap());
+        assert_eq!(store.get_var("KEY").unwrap(), None);
+    }
+
+    #[test]
+    fn test_in_memory_store_clear_all() {
+        let store = InMemorySecretStore::new();
+        store.set_var("A", "1").unwrap();
+        store.set_var("B", "2").unwrap();
+        store.clear_all().unwrap();
+        assert!(store.list_vars().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_encrypted_file_store_roundtrip_and_wrong_passphrase() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::env::set_var("LPOP_STORE_DIR", dir.path());
+
+        let store = EncryptedFileSecretStore::new("svc", "correct horse".to_string()).unwrap();
+        store.set_var("KEY", "value").unwrap();
+        assert_eq!(store.get_var("KEY").unwrap(), Some("value".to_string()));
+
+        let wrong = EncryptedFileSecretStore::new("svc", "wrong horse".to_string()).unwrap();
+        assert!(wrong.get_var("KEY").is_err());
+
+        std::env::remove_var("LPOP_STORE_DIR");
+    }
+}
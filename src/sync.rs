@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use git2::{Cred, FetchOptions, PushOptions, RemoteCallbacks, Repository, Signature};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::vault;
+
+/// Dedicated ref `lpop sync` keeps its encrypted bundles under, so team
+/// secrets travel through the same git remote as the code without
+/// polluting the branch history anyone actually checks out. Each environment
+/// is one file (`<env>.lpop`) in the ref's tree.
+const SYNC_REF: &str = "refs/lpop/sync";
+
+/// Set `LPOP_SYNC_OFFLINE=1` to make `push`/`pull` skip the network entirely
+/// and operate on the local ref only, so tests can exercise the tree/commit
+/// plumbing without a real remote or SSH agent.
+fn offline() -> bool {
+    std::env::var("LPOP_SYNC_OFFLINE").map(|v| v == "1").unwrap_or(false)
+}
+
+fn open_repo(working_dir: Option<PathBuf>) -> Result<Repository> {
+    let dir = working_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
+    Repository::open(&dir).with_context(|| format!("Failed to open git repo at {}", dir.display()))
+}
+
+fn ssh_callbacks() -> RemoteCallbacks<'static> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|_url, username_from_url, _allowed_types| {
+        Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"))
+    });
+    callbacks
+}
+
+/// Seals `vars` with `passphrase` and commits the resulting bundle as
+/// `<env>.lpop` onto `SYNC_REF`, then pushes that ref to `remote_name`
+/// (`origin` by default) unless sync is offline.
+pub fn push(
+    working_dir: Option<PathBuf>,
+    remote_name: Option<String>,
+    env: &str,
+    passphrase: &str,
+    vars: &HashMap<String, String>,
+) -> Result<()> {
+    let repo = open_repo(working_dir)?;
+
+    let tmp = tempfile::NamedTempFile::new()?;
+    vault::export_bundle(vars, passphrase, tmp.path())?;
+    let bundle_bytes = std::fs::read(tmp.path())?;
+    let blob_oid = repo.blob(&bundle_bytes)?;
+
+    // Start from the existing sync tree (if any) so other environments'
+    // bundles already committed to the ref are preserved.
+    let mut tree_builder = match repo.find_reference(SYNC_REF).and_then(|r| r.peel_to_tree()) {
+        Ok(tree) => repo.treebuilder(Some(&tree))?,
+        Err(_) => repo.treebuilder(None)?,
+    };
+    tree_builder.insert(format!("{}.lpop", env), blob_oid, 0o100644)?;
+    let tree_oid = tree_builder.write()?;
+    let tree = repo.find_tree(tree_oid)?;
+
+    let signature = Signature::now("lpop", "lpop@localhost")?;
+    let parent_commit = repo.find_reference(SYNC_REF).ok().and_then(|r| r.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
+
+    repo.commit(
+        Some(SYNC_REF),
+        &signature,
+        &signature,
+        &format!("Sync {} environment", env),
+        &tree,
+        &parents,
+    )?;
+
+    if offline() {
+        return Ok(());
+    }
+
+    let remote_name = remote_name.unwrap_or_else(|| "origin".to_string());
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .with_context(|| format!("Failed to find remote '{}'", remote_name))?;
+
+    let mut push_options = PushOptions::new();
+    push_options.remote_callbacks(ssh_callbacks());
+    remote
+        .push(&[&format!("{0}:{0}", SYNC_REF)], Some(&mut push_options))
+        .with_context(|| format!("Failed to push {} to {}", SYNC_REF, remote_name))?;
+
+    Ok(())
+}
+
+/// Fetches `SYNC_REF` from `remote_name` (unless offline) and returns the
+/// decrypted variables for `env`, or `None` if no bundle has been synced for
+/// it yet.
+pub fn pull(
+    working_dir: Option<PathBuf>,
+    remote_name: Option<String>,
+    env: &str,
+    passphrase: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let repo = open_repo(working_dir)?;
+    let remote_name = remote_name.unwrap_or_else(|| "origin".to_string());
+
+    if !offline() {
+        let mut remote = repo
+            .find_remote(&remote_name)
+            .with_context(|| format!("Failed to find remote '{}'", remote_name))?;
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(ssh_callbacks());
+        remote
+            .fetch(&[SYNC_REF], Some(&mut fetch_options), None)
+            .with_context(|| format!("Failed to fetch {} from {}", SYNC_REF, remote_name))?;
+
+        let fetch_head = repo.find_reference("FETCH_HEAD")?;
+        let commit = fetch_head.peel_to_commit()?;
+        repo.reference(SYNC_REF, commit.id(), true, "lpop sync pull")?;
+    }
+
+    let Ok(reference) = repo.find_reference(SYNC_REF) else {
+        return Ok(None);
+    };
+    let tree = reference.peel_to_tree()?;
+
+    let entry_name = format!("{}.lpop", env);
+    let Some(entry) = tree.get_name(&entry_name) else {
+        return Ok(None);
+    };
+    let blob = repo.find_blob(entry.id())?;
+
+    let tmp = tempfile::NamedTempFile::new()?;
+    std::fs::write(tmp.path(), blob.content())?;
+    let vars = vault::import_bundle(passphrase, tmp.path())?;
+
+    Ok(Some(vars))
+}
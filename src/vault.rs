@@ -0,0 +1,241 @@
+use aes_gcm::aead::Aead as _;
+use aes_gcm::{Aes256Gcm, KeyInit as _, Nonce};
+use anyhow::{bail, Context, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+const BUNDLE_VERSION: u8 = 1;
+const BUNDLE_NONCE_LEN: usize = 12;
+
+/// On-disk shape of a `lpop vault` file: everything needed to re-derive the
+/// key and authenticate the payload, but never the passphrase itself.
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf_params: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KdfParams {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // argon2's own recommended interactive defaults.
+        Self {
+            m_cost: 19456,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// Serializes a service's env vars into a single encrypted, portable file
+/// (Argon2id-derived key, XChaCha20-Poly1305 AEAD) so they can be backed up
+/// or moved between machines without relying on OS keychain sync.
+pub fn export_vars(vars: &HashMap<String, String>, passphrase: &str, path: &Path) -> Result<()> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let kdf_params = KdfParams::default();
+    let key = derive_key(passphrase, &salt, &kdf_params)?;
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let cipher = XChaCha20Poly1305::new_from_slice(&key).context("invalid derived key length")?;
+    let plaintext = serde_json::to_vec(vars).context("failed to serialize variables")?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce_bytes), plaintext.as_ref())
+        .map_err(|e| anyhow::anyhow!("failed to encrypt vault: {}", e))?;
+
+    let file = VaultFile {
+        version: VAULT_VERSION,
+        kdf_params,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce_bytes),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+
+    fs::write(path, serde_json::to_string_pretty(&file)?)
+        .with_context(|| format!("Failed to write vault file: {}", path.display()))?;
+    Ok(())
+}
+
+/// Reverses `export_vars`, rejecting a wrong passphrase or corrupt file
+/// rather than returning garbage.
+pub fn import_vars(passphrase: &str, path: &Path) -> Result<HashMap<String, String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read vault file: {}", path.display()))?;
+    let file: VaultFile = serde_json::from_str(&content).context("Invalid vault file format")?;
+
+    if file.version != VAULT_VERSION {
+        bail!("Unsupported vault version: {}", file.version);
+    }
+
+    let salt = BASE64
+        .decode(&file.salt)
+        .context("Invalid vault file: bad salt encoding")?;
+    let nonce_bytes = BASE64
+        .decode(&file.nonce)
+        .context("Invalid vault file: bad nonce encoding")?;
+    let ciphertext = BASE64
+        .decode(&file.ciphertext)
+        .context("Invalid vault file: bad ciphertext encoding")?;
+
+    let key = derive_key(passphrase, &salt, &file.kdf_params)?;
+    let cipher = XChaCha20Poly1305::new_from_slice(&key).context("invalid derived key length")?;
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupt vault file"))?;
+
+    serde_json::from_slice(&plaintext).context("Failed to parse decrypted vault contents")
+}
+
+/// Seals `vars` into a single self-contained bundle file for moving secrets
+/// between machines without trusting any cloud storage in between. Unlike
+/// `export_vars`'s JSON format, this is a compact binary layout —
+/// `[version(1) | salt(16) | nonce(12) | ciphertext+tag]` encrypted with
+/// AES-256-GCM — kept around because `lpop export`/`import` already shipped
+/// it and existing bundle files on disk need to keep decrypting. Shares the
+/// same Argon2id key derivation as `export_vars`, just with AES-256-GCM's
+/// shorter nonce instead of XChaCha20-Poly1305's.
+pub fn export_bundle(vars: &HashMap<String, String>, passphrase: &str, path: &Path) -> Result<()> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt, &KdfParams::default())?;
+
+    let mut nonce_bytes = [0u8; BUNDLE_NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let cipher = Aes256Gcm::new_from_slice(&key).context("invalid derived key length")?;
+    let plaintext = serde_json::to_vec(vars).context("failed to serialize variables")?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_ref())
+        .map_err(|e| anyhow::anyhow!("failed to encrypt bundle: {}", e))?;
+
+    let mut bytes = Vec::with_capacity(1 + SALT_LEN + BUNDLE_NONCE_LEN + ciphertext.len());
+    bytes.push(BUNDLE_VERSION);
+    bytes.extend_from_slice(&salt);
+    bytes.extend_from_slice(&nonce_bytes);
+    bytes.extend_from_slice(&ciphertext);
+
+    fs::write(path, bytes).with_context(|| format!("Failed to write bundle: {}", path.display()))?;
+    Ok(())
+}
+
+/// Reverses `export_bundle`: re-derives the key from the prompted passphrase
+/// and the salt read back from the file, then verifies the GCM tag before
+/// returning the variables.
+pub fn import_bundle(passphrase: &str, path: &Path) -> Result<HashMap<String, String>> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read bundle: {}", path.display()))?;
+    if bytes.len() < 1 + SALT_LEN + BUNDLE_NONCE_LEN {
+        bail!("Bundle file is too short to be valid");
+    }
+    if bytes[0] != BUNDLE_VERSION {
+        bail!("Unsupported bundle version: {}", bytes[0]);
+    }
+
+    let salt = &bytes[1..1 + SALT_LEN];
+    let nonce_bytes = &bytes[1 + SALT_LEN..1 + SALT_LEN + BUNDLE_NONCE_LEN];
+    let ciphertext = &bytes[1 + SALT_LEN + BUNDLE_NONCE_LEN..];
+
+    let key = derive_key(passphrase, salt, &KdfParams::default())?;
+    let cipher = Aes256Gcm::new_from_slice(&key).context("invalid derived key length")?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupt bundle file"))?;
+
+    serde_json::from_slice(&plaintext).context("Failed to parse decrypted bundle contents")
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: &KdfParams) -> Result<[u8; 32]> {
+    let argon2_params = argon2::Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| anyhow::anyhow!("invalid KDF parameters: {}", e))?;
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, argon2_params);
+
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_then_import_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.json");
+
+        let mut vars = HashMap::new();
+        vars.insert("API_KEY".to_string(), "super-secret".to_string());
+        vars.insert("DB_URL".to_string(), "postgres://localhost".to_string());
+
+        export_vars(&vars, "correct horse battery staple", &path).unwrap();
+        let restored = import_vars("correct horse battery staple", &path).unwrap();
+
+        assert_eq!(restored, vars);
+    }
+
+    #[test]
+    fn test_import_with_wrong_passphrase_fails() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.json");
+
+        let mut vars = HashMap::new();
+        vars.insert("KEY".to_string(), "value".to_string());
+        export_vars(&vars, "right-passphrase", &path).unwrap();
+
+        let result = import_vars("wrong-passphrase", &path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bundle_export_then_import_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bundle.lpop");
+
+        let mut vars = HashMap::new();
+        vars.insert("API_KEY".to_string(), "super-secret".to_string());
+        vars.insert("DB_URL".to_string(), "postgres://localhost".to_string());
+
+        export_bundle(&vars, "correct horse battery staple", &path).unwrap();
+        let restored = import_bundle("correct horse battery staple", &path).unwrap();
+
+        assert_eq!(restored, vars);
+    }
+
+    #[test]
+    fn test_bundle_import_with_wrong_passphrase_fails() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bundle.lpop");
+
+        let mut vars = HashMap::new();
+        vars.insert("KEY".to_string(), "value".to_string());
+        export_bundle(&vars, "right-passphrase", &path).unwrap();
+
+        let result = import_bundle("wrong-passphrase", &path);
+        assert!(result.is_err());
+    }
+}